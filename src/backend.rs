@@ -0,0 +1,115 @@
+use std::cmp::max;
+
+// Everything least needs from a terminal, so the same WindowState can draw to curses or to a screen kept in memory
+// Method names follow curses, since that's what least was first written against
+
+// A key (or mouse event) read from the terminal, named the way curses names them
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Input {
+    Character(char),
+    KeyUp,
+    KeyDown,
+    KeyLeft,
+    KeyRight,
+    KeyHome,
+    KeyEnd,
+    KeyNPage,
+    KeyPPage,
+    KeyBackspace,
+    KeyDC,
+    // Details of the event are read with Backend::getmouse()
+    KeyMouse,
+    // Any other key, which least ignores
+    Unknown,
+}
+
+// What happened in a mouse event, and where
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MouseEvent {
+    WheelUp,
+    WheelDown,
+    Click { y: i32, x: i32 },
+}
+
+// Text attributes, which can be combined with |
+pub type Attributes = u32;
+pub const A_NORMAL: Attributes = 0;
+pub const A_REVERSE: Attributes = 1;
+pub const A_BOLD: Attributes = 1 << 1;
+pub const A_UNDERLINE: Attributes = 1 << 2;
+
+// Colors for init_pair(), numbered the same as the standard terminal colors
+pub const COLOR_BLACK: i16 = 0;
+pub const COLOR_RED: i16 = 1;
+pub const COLOR_GREEN: i16 = 2;
+pub const COLOR_YELLOW: i16 = 3;
+pub const COLOR_BLUE: i16 = 4;
+pub const COLOR_MAGENTA: i16 = 5;
+pub const COLOR_CYAN: i16 = 6;
+pub const COLOR_WHITE: i16 = 7;
+
+// A screen that least draws to and reads keys from
+// Coordinates are (row, column) from the top left, and drawing only shows up once refresh() is called
+pub trait Backend {
+    // Number of rows and columns on the screen
+    fn size(&self) -> (i32, i32);
+    // Read the next key, waiting for one unless nodelay is on
+    // Returns None if there isn't one (ex: with nodelay on, or once a scripted backend runs out of keys)
+    fn getch(&self) -> Option<Input>;
+    // Whether there are no more keys to come (ex: a scripted backend that's run out), which quits least
+    fn closed(&self) -> bool {
+        false
+    }
+    // Put a key back, so it's the next one getch() returns
+    fn ungetch(&self, input: &Input);
+    // Whether getch() returns straight away instead of waiting for a key
+    fn nodelay(&self, enabled: bool);
+    // In raw mode Ctrl-C comes through getch() instead of killing least
+    fn set_raw(&self, enabled: bool);
+    // Turn capturing of mouse events on or off
+    fn set_mouse(&self, enabled: bool);
+    // The mouse event that the last Input::KeyMouse was for
+    fn getmouse(&self) -> Option<MouseEvent>;
+    // Ask the terminal to wrap pasted text in Esc [ 2 0 0 ~ and Esc [ 2 0 1 ~, or stop asking
+    fn set_bracketed_paste(&self, enabled: bool);
+    // Set the foreground and background color of a color pair
+    fn init_pair(&self, pair: i16, foreground: i16, background: i16);
+    // Attributes and color pair that text is drawn in from now on
+    fn attrset(&self, attributes: Attributes, pair: i16);
+    fn mv(&self, y: i32, x: i32);
+    // Draw at most n chars of text at the cursor, moving the cursor past them
+    fn addnstr(&self, text: &str, n: usize);
+    // Blank the rest of the cursor's row
+    fn clrtoeol(&self);
+    // Change the attributes and color pair of n chars already on the screen, without changing the text
+    fn mvchgat(&self, y: i32, x: i32, n: i32, attributes: Attributes, pair: i16);
+    // Blank the whole screen
+    fn erase(&self);
+    fn refresh(&self);
+    fn beep(&self);
+    // Give the terminal back (ex: to run an editor, or when least quits), the next refresh() takes it over again
+    fn endwin(&self);
+
+    fn addstr(&self, text: &str) {
+        self.addnstr(text, usize::MAX);
+    }
+
+    fn mvaddstr(&self, y: i32, x: i32, text: &str) {
+        self.mv(y, x);
+        self.addstr(text);
+    }
+
+    fn mvaddnstr(&self, y: i32, x: i32, text: &str, n: i32) {
+        self.mv(y, x);
+        self.addnstr(text, max(0, n) as usize);
+    }
+
+    fn mvaddch(&self, y: i32, x: i32, c: char) {
+        self.mvaddstr(y, x, &c.to_string());
+    }
+
+    // Blank the whole screen, and redraw all of it on the next refresh() instead of just what changed
+    fn clear(&self) {
+        self.erase();
+    }
+}
@@ -0,0 +1,117 @@
+use std::{
+    fs::{metadata, read_dir},
+    io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::timestamp::civil_from_days;
+
+// A single row of a directory listing
+#[derive(Clone)]
+pub struct Entry {
+    pub name: String,
+    pub path: PathBuf,
+    pub is_dir: bool,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+// The contents of a directory being browsed, in display order
+#[derive(Clone)]
+pub struct DirListing {
+    pub dir: PathBuf,
+    pub entries: Vec<Entry>,
+}
+
+impl DirListing {
+    // Read a directory into a listing, with a "../" entry first (unless at the root), then subdirectories, then files
+    // Entries that can't be stat'ed (ex: broken symlinks) are still listed, just without a size or mtime
+    pub fn read(dir: &Path) -> io::Result<DirListing> {
+        let dir: PathBuf = dir.canonicalize()?;
+        let mut entries: Vec<Entry> = Vec::new();
+        for e in read_dir(&dir)? {
+            let e = e?;
+            let path: PathBuf = e.path();
+            // metadata() follows symlinks, so a link to a directory can be descended into
+            let md = metadata(&path).ok();
+            entries.push(Entry {
+                name: e.file_name().to_string_lossy().to_string(),
+                path,
+                is_dir: md.as_ref().map(|m| m.is_dir()).unwrap_or(false),
+                size: md.as_ref().map(|m| m.len()).unwrap_or(0),
+                modified: md.and_then(|m| m.modified().ok()),
+            });
+        }
+        entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+        if let Some(parent) = dir.parent() {
+            entries.insert(
+                0,
+                Entry {
+                    name: String::from(".."),
+                    path: parent.to_path_buf(),
+                    is_dir: true,
+                    size: 0,
+                    modified: metadata(parent).and_then(|m| m.modified()).ok(),
+                },
+            );
+        }
+        Ok(DirListing { dir, entries })
+    }
+
+    // Render the listing as lines for the screen: a header with the directory path, then one line per entry
+    // Ex: "2020-06-01 14:03     4.2K  notes.txt"
+    pub fn to_lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = vec![format!("{}:", self.dir.display())];
+        for entry in &self.entries {
+            let modified: String = match entry.modified {
+                Some(time) => format_time(time),
+                None => String::from("????-??-?? ??:??"),
+            };
+            let size: String = if entry.is_dir {
+                String::from("-")
+            } else {
+                format_size(entry.size)
+            };
+            let suffix: &str = if entry.is_dir { "/" } else { "" };
+            lines.push(format!(
+                "{}  {:>7}  {}{}",
+                modified, size, entry.name, suffix
+            ));
+        }
+        lines
+    }
+}
+
+// Human readable file size, ex: 512B, 4.2K, 13.0M
+fn format_size(size: u64) -> String {
+    if size < 1024 {
+        return format!("{}B", size);
+    }
+    let mut scaled: f64 = size as f64;
+    for unit in &["K", "M", "G", "T"] {
+        scaled /= 1024.0;
+        if scaled < 1024.0 {
+            return format!("{:.1}{}", scaled, unit);
+        }
+    }
+    format!("{:.1}P", scaled / 1024.0)
+}
+
+// Format a time as "YYYY-MM-DD HH:MM" in UTC, without pulling in a date library
+fn format_time(time: SystemTime) -> String {
+    let secs: i64 = match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    };
+    let (year, month, day) = civil_from_days(secs.div_euclid(86400));
+    let secs_of_day: i64 = secs.rem_euclid(86400);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        (secs_of_day % 3600) / 60
+    )
+}
@@ -0,0 +1,60 @@
+use std::{path::PathBuf, rc::Rc, sync::Arc};
+
+use crate::{
+    browser::DirListing, diff::DiffView, json::JsonView, loglevel::LogView, search::MatchList,
+    table::Table,
+};
+
+// Everything about an open file (or command output, directory listing, etc) that needs to survive being switched away from
+// The buffer being viewed lives directly in WindowState, this only holds the ones in the background
+pub struct Buffer {
+    pub name: String,
+    // Where the lines were read from, None for things like command output that don't exist on disk
+    pub path: Option<PathBuf>,
+    pub lines: Arc<Vec<String>>,
+    pub content_top: i32,
+    pub search_results: Vec<(i32, i32, i32)>,
+    pub search_index: Option<usize>,
+    pub search_reverse: bool,
+    pub search_hidden: bool,
+    pub listing: Option<DirListing>,
+    pub selected: i32,
+    // Set on the results of a search across every buffer, see search::MatchList
+    pub matches: Option<MatchList>,
+    pub mark: Option<i32>,
+    // Color pair to draw each line in, empty when the lines aren't colored
+    pub line_colors: Vec<i16>,
+    // Set on both sides of a diff, see diff::DiffView
+    pub diff: Option<Rc<DiffView>>,
+    // Set when the lines are a pretty printed JSON document, see json::JsonView
+    pub json: Option<JsonView>,
+    // Set when the lines are the rows of a delimited file drawn as a table, see table::Table
+    pub table: Option<Table>,
+    // Set when the lines are a log, with each line's level and whether lines below a level are hidden, see loglevel::LogView
+    pub log: Option<LogView>,
+}
+
+impl Buffer {
+    // Constructor for a fresh buffer, viewed from the top
+    pub fn new(name: String, path: Option<PathBuf>, lines: Vec<String>) -> Buffer {
+        Buffer {
+            name,
+            path,
+            lines: Arc::new(lines),
+            content_top: 0,
+            search_results: Vec::new(),
+            search_index: None,
+            search_reverse: false,
+            search_hidden: false,
+            listing: None,
+            selected: 0,
+            matches: None,
+            mark: None,
+            line_colors: Vec::new(),
+            diff: None,
+            json: None,
+            table: None,
+            log: None,
+        }
+    }
+}
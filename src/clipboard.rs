@@ -0,0 +1,41 @@
+use std::{
+    env,
+    io::{self, Write},
+};
+
+const BASE64_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+// Standard (padded) base64, which is all OSC 52 needs, so there's no point pulling in a crate for it
+fn base64(bytes: &[u8]) -> String {
+    let mut encoded: String = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b: [u32; 3] = [
+            chunk[0] as u32,
+            *chunk.get(1).unwrap_or(&0) as u32,
+            *chunk.get(2).unwrap_or(&0) as u32,
+        ];
+        let triple: u32 = (b[0] << 16) | (b[1] << 8) | b[2];
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64_CHARS[((triple >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+// Copy text to the system clipboard with the OSC 52 escape sequence
+// The terminal does the copying, so this works over SSH and without an X server
+// Inside tmux, the sequence is wrapped so tmux passes it through to the outer terminal
+pub fn copy_osc52(text: &str) -> io::Result<()> {
+    let sequence: String = format!("\u{1b}]52;c;{}\u{7}", base64(text.as_bytes()));
+    let mut stdout = io::stdout();
+    if env::var_os("TMUX").is_some() {
+        write!(stdout, "\u{1b}Ptmux;\u{1b}{}\u{1b}\\", sequence)?;
+    } else {
+        write!(stdout, "{}", sequence)?;
+    }
+    stdout.flush()
+}
@@ -0,0 +1,70 @@
+use std::fs::read_dir;
+
+use shellexpand::full;
+
+// Split a partially typed path into the directory part (kept exactly as typed) and the name prefix
+// Ex: "~/Documents/no" => ("~/Documents/", "no")
+fn split_input(input: &str) -> (&str, &str) {
+    match input.rfind('/') {
+        Some(i) => input.split_at(i + 1),
+        None => ("", input),
+    }
+}
+
+// Find every entry in the typed directory whose name starts with the typed prefix
+// The directory is expanded (tildes and vars) before reading, but the returned completions keep the user's spelling
+// Returns a sorted Vec of full completions, with a trailing "/" on directories
+pub fn complete_path(input: &str) -> Vec<String> {
+    let (typed_dir, prefix) = split_input(input);
+    let dir: String = if typed_dir.is_empty() {
+        String::from(".")
+    } else {
+        match full(typed_dir) {
+            Ok(expanded) => expanded.to_string(),
+            Err(_) => return Vec::new(),
+        }
+    };
+    let entries = match read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut candidates: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name: String = e.file_name().to_string_lossy().to_string();
+            // Hidden files are only offered once the user has typed the leading dot
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+            // metadata() follows symlinks, so a link to a directory completes like a directory
+            let is_dir: bool = e.path().metadata().map(|md| md.is_dir()).unwrap_or(false);
+            let suffix: &str = if is_dir { "/" } else { "" };
+            Some(format!("{}{}{}", typed_dir, name, suffix))
+        })
+        .collect();
+    candidates.sort();
+    candidates
+}
+
+// Longest string that every candidate starts with, used to extend ambiguous input on the first Tab
+pub fn common_prefix(candidates: &[String]) -> String {
+    let mut prefix: String = match candidates.first() {
+        Some(first) => first.clone(),
+        None => return String::new(),
+    };
+    for candidate in &candidates[1..] {
+        while !candidate.starts_with(&prefix) {
+            prefix.pop();
+        }
+    }
+    prefix
+}
+
+// The part of a completion that's shown in the candidate popup, aka the entry name without its directory
+pub fn display_name(candidate: &str) -> &str {
+    let trimmed: &str = candidate.strip_suffix('/').unwrap_or(candidate);
+    match trimmed.rfind('/') {
+        Some(i) => &candidate[i + 1..],
+        None => candidate,
+    }
+}
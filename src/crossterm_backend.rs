@@ -0,0 +1,362 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    io::{self, Write},
+    process,
+    time::Duration,
+};
+
+use crossterm::{
+    cursor::{MoveTo, Show},
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEventKind,
+    },
+    queue,
+    style::{Attribute, Color, Colors, Print, SetAttribute, SetColors},
+    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+
+use crate::{
+    backend::{self, Attributes, Backend, Input, MouseEvent},
+    grid::{Cell, Grid},
+};
+
+// Stands in for what's on the terminal when it isn't known (ex: after clear()), so every cell gets drawn
+const UNKNOWN: Cell = Cell {
+    ch: '\u{0}',
+    attributes: backend::A_NORMAL,
+    pair: -1,
+};
+
+struct State {
+    grid: Grid,
+    // What the terminal is showing, compared against the grid on refresh() so only the cells that changed get drawn
+    shown: Vec<Cell>,
+    pairs: HashMap<i16, (i16, i16)>,
+    // Keys already read that getch() hasn't returned yet (ex: the key after an Alt, which curses sends as Esc then the key)
+    pending: VecDeque<Input>,
+    mouse: Option<MouseEvent>,
+    mouse_enabled: bool,
+    nodelay: bool,
+    raw: bool,
+    // Set by endwin(), until the next refresh() takes the terminal over again
+    suspended: bool,
+}
+
+// The real terminal, through crossterm, which is pure Rust so least can be built without ncurses
+// Drawing goes to a grid in memory the same way it does with curses, and refresh() sends the terminal whatever changed
+pub struct CrosstermBackend {
+    state: RefCell<State>,
+}
+
+impl CrosstermBackend {
+    // Take over the terminal
+    pub fn new() -> CrosstermBackend {
+        let (cols, rows) = terminal::size().unwrap_or((80, 24));
+        let backend: CrosstermBackend = CrosstermBackend {
+            state: RefCell::new(State {
+                grid: Grid::new(rows as i32, cols as i32),
+                shown: Vec::new(),
+                pairs: HashMap::new(),
+                pending: VecDeque::new(),
+                mouse: None,
+                mouse_enabled: false,
+                nodelay: false,
+                raw: false,
+                suspended: true,
+            }),
+        };
+        backend.resume();
+        backend
+    }
+
+    // Put the terminal into the state least draws in: raw input on the alternate screen, redrawn from scratch
+    fn resume(&self) {
+        let mut state = self.state.borrow_mut();
+        terminal::enable_raw_mode().ok();
+        let mut stdout = io::stdout();
+        queue!(stdout, EnterAlternateScreen, Clear(ClearType::All)).ok();
+        if state.mouse_enabled {
+            queue!(stdout, EnableMouseCapture).ok();
+        }
+        stdout.flush().ok();
+        state.shown = vec![UNKNOWN; state.grid.cells.len()];
+        state.suspended = false;
+    }
+
+    // Translate a crossterm event into the keys curses would have read for it
+    // Returns None for events least doesn't use (ex: mouse movement and key releases)
+    fn translate(&self, event: Event) -> Option<Input> {
+        let mut state = self.state.borrow_mut();
+        match event {
+            Event::Key(KeyEvent {
+                code,
+                modifiers,
+                kind,
+                ..
+            }) if kind != KeyEventKind::Release => {
+                let input: Input = match code {
+                    KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => {
+                        Input::Character(((c.to_ascii_uppercase() as u8) & 0x1f) as char)
+                    }
+                    KeyCode::Char(c) => Input::Character(c),
+                    KeyCode::Enter => Input::Character('\n'),
+                    KeyCode::Tab => Input::Character('\t'),
+                    KeyCode::Esc => Input::Character('\u{1b}'),
+                    KeyCode::Backspace => Input::KeyBackspace,
+                    KeyCode::Delete => Input::KeyDC,
+                    KeyCode::Up => Input::KeyUp,
+                    KeyCode::Down => Input::KeyDown,
+                    KeyCode::Left => Input::KeyLeft,
+                    KeyCode::Right => Input::KeyRight,
+                    KeyCode::Home => Input::KeyHome,
+                    KeyCode::End => Input::KeyEnd,
+                    KeyCode::PageUp => Input::KeyPPage,
+                    KeyCode::PageDown => Input::KeyNPage,
+                    _ => Input::Unknown,
+                };
+                if modifiers.contains(KeyModifiers::ALT) {
+                    state.pending.push_back(input);
+                    Some(Input::Character('\u{1b}'))
+                } else {
+                    Some(input)
+                }
+            }
+            Event::Mouse(mouse) if state.mouse_enabled => {
+                state.mouse = match mouse.kind {
+                    MouseEventKind::ScrollUp => Some(MouseEvent::WheelUp),
+                    MouseEventKind::ScrollDown => Some(MouseEvent::WheelDown),
+                    MouseEventKind::Down(MouseButton::Left) => Some(MouseEvent::Click {
+                        y: mouse.row as i32,
+                        x: mouse.column as i32,
+                    }),
+                    _ => return None,
+                };
+                Some(Input::KeyMouse)
+            }
+            // Crossterm collects a bracketed paste itself, so hand it over wrapped the way the terminal sent it
+            Event::Paste(text) => {
+                let wrapped: String = format!("[200~{}\u{1b}[201~", text);
+                state.pending.extend(wrapped.chars().map(Input::Character));
+                Some(Input::Character('\u{1b}'))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Default for CrosstermBackend {
+    fn default() -> CrosstermBackend {
+        CrosstermBackend::new()
+    }
+}
+
+// The crossterm color for one of the colors numbered in backend
+fn color(number: i16) -> Color {
+    match number {
+        backend::COLOR_BLACK => Color::Black,
+        backend::COLOR_RED => Color::DarkRed,
+        backend::COLOR_GREEN => Color::DarkGreen,
+        backend::COLOR_YELLOW => Color::DarkYellow,
+        backend::COLOR_BLUE => Color::DarkBlue,
+        backend::COLOR_MAGENTA => Color::DarkMagenta,
+        backend::COLOR_CYAN => Color::DarkCyan,
+        backend::COLOR_WHITE => Color::Grey,
+        _ => Color::Reset,
+    }
+}
+
+impl Backend for CrosstermBackend {
+    fn size(&self) -> (i32, i32) {
+        let state = self.state.borrow();
+        (state.grid.rows, state.grid.cols)
+    }
+
+    fn getch(&self) -> Option<Input> {
+        if let Some(input) = self.state.borrow_mut().pending.pop_front() {
+            return Some(input);
+        }
+        loop {
+            let nodelay: bool = self.state.borrow().nodelay;
+            if nodelay && !event::poll(Duration::ZERO).unwrap_or(false) {
+                return None;
+            }
+            let input: Input = match self.translate(event::read().ok()?) {
+                Some(input) => input,
+                None => continue,
+            };
+            // Outside of raw mode Ctrl-C interrupts least, the same as it does under curses
+            if input == Input::Character('\u{3}') && !self.state.borrow().raw {
+                self.endwin();
+                process::exit(130);
+            }
+            return Some(input);
+        }
+    }
+
+    fn ungetch(&self, input: &Input) {
+        self.state.borrow_mut().pending.push_front(*input);
+    }
+
+    fn nodelay(&self, enabled: bool) {
+        self.state.borrow_mut().nodelay = enabled;
+    }
+
+    // The terminal is always in raw mode underneath, this just decides what Ctrl-C does
+    fn set_raw(&self, enabled: bool) {
+        self.state.borrow_mut().raw = enabled;
+    }
+
+    fn set_mouse(&self, enabled: bool) {
+        let mut state = self.state.borrow_mut();
+        state.mouse_enabled = enabled;
+        if !state.suspended {
+            let mut stdout = io::stdout();
+            if enabled {
+                queue!(stdout, EnableMouseCapture).ok();
+            } else {
+                queue!(stdout, DisableMouseCapture).ok();
+            }
+            stdout.flush().ok();
+        }
+    }
+
+    fn getmouse(&self) -> Option<MouseEvent> {
+        self.state.borrow().mouse
+    }
+
+    fn set_bracketed_paste(&self, enabled: bool) {
+        let mut stdout = io::stdout();
+        if enabled {
+            queue!(stdout, EnableBracketedPaste).ok();
+        } else {
+            queue!(stdout, DisableBracketedPaste).ok();
+        }
+        stdout.flush().ok();
+    }
+
+    fn init_pair(&self, pair: i16, foreground: i16, background: i16) {
+        self.state
+            .borrow_mut()
+            .pairs
+            .insert(pair, (foreground, background));
+    }
+
+    fn attrset(&self, attributes: Attributes, pair: i16) {
+        self.state.borrow_mut().grid.attrset(attributes, pair);
+    }
+
+    fn mv(&self, y: i32, x: i32) {
+        self.state.borrow_mut().grid.mv(y, x);
+    }
+
+    fn addnstr(&self, text: &str, n: usize) {
+        self.state.borrow_mut().grid.addnstr(text, n);
+    }
+
+    fn clrtoeol(&self) {
+        self.state.borrow_mut().grid.clrtoeol();
+    }
+
+    fn mvchgat(&self, y: i32, x: i32, n: i32, attributes: Attributes, pair: i16) {
+        self.state
+            .borrow_mut()
+            .grid
+            .mvchgat(y, x, n, attributes, pair);
+    }
+
+    fn erase(&self) {
+        self.state.borrow_mut().grid.erase();
+    }
+
+    fn refresh(&self) {
+        if self.state.borrow().suspended {
+            self.resume();
+        }
+        let mut state = self.state.borrow_mut();
+        let state: &mut State = &mut state;
+        let mut stdout = io::stdout();
+        // Where the terminal's cursor is and what it's drawing in, so they're only sent when they change
+        let mut at: Option<(i32, i32)> = None;
+        let mut style: Option<(Attributes, i16)> = None;
+        for y in 0..state.grid.rows {
+            for x in 0..state.grid.cols {
+                let index: usize = (y * state.grid.cols + x) as usize;
+                let cell: Cell = state.grid.cells[index];
+                if state.shown[index] == cell {
+                    continue;
+                }
+                if at != Some((y, x)) {
+                    queue!(stdout, MoveTo(x as u16, y as u16)).ok();
+                }
+                if style != Some((cell.attributes, cell.pair)) {
+                    queue!(stdout, SetAttribute(Attribute::Reset)).ok();
+                    if let Some(&(foreground, background)) = state.pairs.get(&cell.pair) {
+                        queue!(
+                            stdout,
+                            SetColors(Colors::new(color(foreground), color(background)))
+                        )
+                        .ok();
+                    }
+                    if cell.attributes & backend::A_REVERSE != 0 {
+                        queue!(stdout, SetAttribute(Attribute::Reverse)).ok();
+                    }
+                    if cell.attributes & backend::A_BOLD != 0 {
+                        queue!(stdout, SetAttribute(Attribute::Bold)).ok();
+                    }
+                    if cell.attributes & backend::A_UNDERLINE != 0 {
+                        queue!(stdout, SetAttribute(Attribute::Underlined)).ok();
+                    }
+                    style = Some((cell.attributes, cell.pair));
+                }
+                queue!(stdout, Print(cell.ch)).ok();
+                state.shown[index] = cell;
+                at = Some((y, x + 1));
+            }
+        }
+        let (y, x) = state.grid.cursor;
+        queue!(
+            stdout,
+            SetAttribute(Attribute::Reset),
+            MoveTo(x as u16, y as u16)
+        )
+        .ok();
+        stdout.flush().ok();
+    }
+
+    fn beep(&self) {
+        let mut stdout = io::stdout();
+        queue!(stdout, Print('\u{7}')).ok();
+        stdout.flush().ok();
+    }
+
+    fn endwin(&self) {
+        let mut state = self.state.borrow_mut();
+        if state.suspended {
+            return;
+        }
+        let mut stdout = io::stdout();
+        if state.mouse_enabled {
+            queue!(stdout, DisableMouseCapture).ok();
+        }
+        queue!(
+            stdout,
+            SetAttribute(Attribute::Reset),
+            LeaveAlternateScreen,
+            Show
+        )
+        .ok();
+        stdout.flush().ok();
+        terminal::disable_raw_mode().ok();
+        state.suspended = true;
+    }
+
+    fn clear(&self) {
+        let mut state = self.state.borrow_mut();
+        state.grid.erase();
+        let len: usize = state.shown.len();
+        state.shown = vec![UNKNOWN; len];
+        queue!(io::stdout(), Clear(ClearType::All)).ok();
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    env,
+    io::{self, Write},
+};
+
+use pancurses::{chtype, Window};
+
+use crate::backend::{self, Attributes, Backend, Input, MouseEvent};
+
+// The real terminal, through pancurses (ncurses on Unix, PDCurses on Windows)
+pub struct CursesBackend {
+    window: Window,
+}
+
+impl CursesBackend {
+    // Take over the terminal
+    pub fn new() -> CursesBackend {
+        // Curses waits a full second after Esc to see if it starts a key sequence, which makes cancelling prompts sluggish
+        if env::var_os("ESCDELAY").is_none() {
+            env::set_var("ESCDELAY", "25");
+        }
+        let window: Window = pancurses::initscr();
+        window.keypad(true);
+        pancurses::noecho();
+        pancurses::start_color();
+        CursesBackend { window }
+    }
+}
+
+impl Default for CursesBackend {
+    fn default() -> CursesBackend {
+        CursesBackend::new()
+    }
+}
+
+// Translate least's attributes into curses ones
+fn curses_attributes(attributes: Attributes) -> chtype {
+    let mut converted: chtype = pancurses::A_NORMAL;
+    if attributes & backend::A_REVERSE != 0 {
+        converted |= pancurses::A_REVERSE;
+    }
+    if attributes & backend::A_BOLD != 0 {
+        converted |= pancurses::A_BOLD;
+    }
+    if attributes & backend::A_UNDERLINE != 0 {
+        converted |= pancurses::A_UNDERLINE;
+    }
+    converted
+}
+
+impl Backend for CursesBackend {
+    fn size(&self) -> (i32, i32) {
+        (self.window.get_max_y(), self.window.get_max_x())
+    }
+
+    fn getch(&self) -> Option<Input> {
+        self.window.getch().map(|input| match input {
+            pancurses::Input::Character(c) => Input::Character(c),
+            pancurses::Input::KeyUp => Input::KeyUp,
+            pancurses::Input::KeyDown => Input::KeyDown,
+            pancurses::Input::KeyLeft => Input::KeyLeft,
+            pancurses::Input::KeyRight => Input::KeyRight,
+            pancurses::Input::KeyHome => Input::KeyHome,
+            pancurses::Input::KeyEnd => Input::KeyEnd,
+            pancurses::Input::KeyNPage => Input::KeyNPage,
+            pancurses::Input::KeyPPage => Input::KeyPPage,
+            pancurses::Input::KeyBackspace => Input::KeyBackspace,
+            pancurses::Input::KeyDC => Input::KeyDC,
+            pancurses::Input::KeyMouse => Input::KeyMouse,
+            _ => Input::Unknown,
+        })
+    }
+
+    fn ungetch(&self, input: &Input) {
+        let converted: pancurses::Input = match *input {
+            Input::Character(c) => pancurses::Input::Character(c),
+            Input::KeyUp => pancurses::Input::KeyUp,
+            Input::KeyDown => pancurses::Input::KeyDown,
+            Input::KeyLeft => pancurses::Input::KeyLeft,
+            Input::KeyRight => pancurses::Input::KeyRight,
+            Input::KeyHome => pancurses::Input::KeyHome,
+            Input::KeyEnd => pancurses::Input::KeyEnd,
+            Input::KeyNPage => pancurses::Input::KeyNPage,
+            Input::KeyPPage => pancurses::Input::KeyPPage,
+            Input::KeyBackspace => pancurses::Input::KeyBackspace,
+            Input::KeyDC => pancurses::Input::KeyDC,
+            // The event itself has already been read off the terminal, so there's nothing to put back
+            Input::KeyMouse | Input::Unknown => return,
+        };
+        self.window.ungetch(&converted);
+    }
+
+    fn nodelay(&self, enabled: bool) {
+        self.window.nodelay(enabled);
+    }
+
+    fn set_raw(&self, enabled: bool) {
+        if enabled {
+            pancurses::raw();
+        } else {
+            pancurses::noraw();
+            pancurses::cbreak();
+        }
+    }
+
+    fn set_mouse(&self, enabled: bool) {
+        let mask: pancurses::mmask_t = if enabled {
+            pancurses::BUTTON1_PRESSED
+                | pancurses::BUTTON1_CLICKED
+                | pancurses::BUTTON4_PRESSED
+                | pancurses::BUTTON5_PRESSED
+        } else {
+            0
+        };
+        pancurses::mousemask(mask, std::ptr::null_mut());
+    }
+
+    fn getmouse(&self) -> Option<MouseEvent> {
+        let event = pancurses::getmouse().ok()?;
+        if event.bstate & pancurses::BUTTON4_PRESSED != 0 {
+            Some(MouseEvent::WheelUp)
+        } else if event.bstate & pancurses::BUTTON5_PRESSED != 0 {
+            Some(MouseEvent::WheelDown)
+        } else if event.bstate & (pancurses::BUTTON1_PRESSED | pancurses::BUTTON1_CLICKED) != 0 {
+            Some(MouseEvent::Click {
+                y: event.y,
+                x: event.x,
+            })
+        } else {
+            None
+        }
+    }
+
+    fn set_bracketed_paste(&self, enabled: bool) {
+        print!(
+            "{}",
+            if enabled {
+                "\u{1b}[?2004h"
+            } else {
+                "\u{1b}[?2004l"
+            }
+        );
+        io::stdout().flush().ok();
+    }
+
+    fn init_pair(&self, pair: i16, foreground: i16, background: i16) {
+        pancurses::init_pair(pair, foreground, background);
+    }
+
+    fn attrset(&self, attributes: Attributes, pair: i16) {
+        self.window
+            .attrset(curses_attributes(attributes) | pancurses::COLOR_PAIR(pair as chtype));
+    }
+
+    fn mv(&self, y: i32, x: i32) {
+        self.window.mv(y, x);
+    }
+
+    fn addnstr(&self, text: &str, n: usize) {
+        self.window.addnstr(text, n);
+    }
+
+    fn addstr(&self, text: &str) {
+        self.window.addstr(text);
+    }
+
+    fn clrtoeol(&self) {
+        self.window.clrtoeol();
+    }
+
+    fn mvchgat(&self, y: i32, x: i32, n: i32, attributes: Attributes, pair: i16) {
+        self.window
+            .mvchgat(y, x, n, curses_attributes(attributes), pair);
+    }
+
+    fn erase(&self) {
+        self.window.erase();
+    }
+
+    fn refresh(&self) {
+        self.window.refresh();
+    }
+
+    fn beep(&self) {
+        pancurses::beep();
+    }
+
+    fn endwin(&self) {
+        pancurses::endwin();
+    }
+
+    fn clear(&self) {
+        self.window.clear();
+    }
+}
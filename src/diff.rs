@@ -0,0 +1,236 @@
+use std::cmp::max;
+
+// Color pairs used for the rows of a diff, set up in WindowState::start()
+pub const DELETED_COLOR: i16 = 3;
+pub const INSERTED_COLOR: i16 = 4;
+pub const CHANGED_COLOR: i16 = 5;
+
+// Give up on finding the smallest diff after this many edits, and treat what's left as one big change
+// Finding a diff takes time that grows with the number of lines times the number of edits, so this keeps very different files quick
+const MAX_EDITS: isize = 4000;
+
+// Where the hunks of a diff start, shared by the two sides of a diff so they can find each other to scroll in lockstep
+pub struct DiffView {
+    pub hunks: Vec<i32>,
+}
+
+// Both sides of a diff, padded with blank rows so that matching lines are on the same row
+// Each row has a color pair for each side (0 for unchanged rows)
+pub struct AlignedDiff {
+    pub left: Vec<String>,
+    pub right: Vec<String>,
+    pub left_colors: Vec<i16>,
+    pub right_colors: Vec<i16>,
+    pub view: DiffView,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Edit {
+    Equal,
+    Delete,
+    Insert,
+}
+
+// The run of equal lines in the middle of the shortest path of edits from a to b, along with how many edits the whole path has
+// The path goes through start, takes the equal lines to end, and carries on from there
+struct Snake {
+    edits: isize,
+    start: (usize, usize),
+    end: (usize, usize),
+}
+
+// Search for the shortest path from both ends at once until the two searches overlap, see section 4 of the Myers paper
+// Only keeps the furthest reaching x for each diagonal, so it needs memory for the number of lines rather than lines times edits
+// Returns None if the path needs more than 2 * max_d edits
+fn middle_snake(a: &[String], b: &[String], max_d: isize) -> Option<Snake> {
+    let n: isize = a.len() as isize;
+    let m: isize = b.len() as isize;
+    let delta: isize = n - m;
+    let max_d: isize = max_d.min((n + m + 1) / 2);
+    let offset: isize = max_d + 1;
+    // Furthest x reached on each diagonal k = x - y from the start, and furthest u on each diagonal u - v from the end
+    // (u and v count lines back from the ends of a and b)
+    let mut forward: Vec<isize> = vec![0; (2 * max_d + 3) as usize];
+    let mut backward: Vec<isize> = vec![0; (2 * max_d + 3) as usize];
+    for d in 0..=max_d {
+        let mut k: isize = -d;
+        while k <= d {
+            let i: usize = (offset + k) as usize;
+            let mut x: isize = if k == -d || (k != d && forward[i - 1] < forward[i + 1]) {
+                forward[i + 1]
+            } else {
+                forward[i - 1] + 1
+            };
+            let mut y: isize = x - k;
+            let start: (usize, usize) = (x as usize, y as usize);
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[i] = x;
+            // Diagonal k from the start is diagonal delta - k from the end
+            let back_k: isize = delta - k;
+            if delta % 2 != 0 && back_k.abs() < d && x + backward[(offset + back_k) as usize] >= n {
+                return Some(Snake {
+                    edits: 2 * d - 1,
+                    start,
+                    end: (x as usize, y as usize),
+                });
+            }
+            k += 2;
+        }
+        let mut k: isize = -d;
+        while k <= d {
+            let i: usize = (offset + k) as usize;
+            let mut u: isize = if k == -d || (k != d && backward[i - 1] < backward[i + 1]) {
+                backward[i + 1]
+            } else {
+                backward[i - 1] + 1
+            };
+            let mut v: isize = u - k;
+            let end: (usize, usize) = ((n - u) as usize, (m - v) as usize);
+            while u < n && v < m && a[(n - u - 1) as usize] == b[(m - v - 1) as usize] {
+                u += 1;
+                v += 1;
+            }
+            backward[i] = u;
+            let forward_k: isize = delta - k;
+            if delta % 2 == 0
+                && forward_k.abs() <= d
+                && forward[(offset + forward_k) as usize] + u >= n
+            {
+                return Some(Snake {
+                    edits: 2 * d,
+                    start: ((n - u) as usize, (m - v) as usize),
+                    end,
+                });
+            }
+            k += 2;
+        }
+    }
+    None
+}
+
+// Myers' O(ND) diff of two slices of lines, returning the edits that turn a into b
+// See http://www.xmailserver.org/diff2.pdf, this is the linear space version that splits the files at the middle snake
+// Returns None if the files need more than MAX_EDITS edits
+fn myers(a: &[String], b: &[String]) -> Option<Vec<Edit>> {
+    let mut edits: Vec<Edit> = Vec::new();
+    let snake: Snake = middle_snake(a, b, (MAX_EDITS + 1) / 2)?;
+    if snake.edits > MAX_EDITS {
+        return None;
+    }
+    diff_around(a, b, snake, &mut edits);
+    Some(edits)
+}
+
+// Add the edits that turn a into b, finding them on either side of the middle snake
+fn diff_into(a: &[String], b: &[String], edits: &mut Vec<Edit>) {
+    if a.is_empty() || b.is_empty() {
+        edits.extend(vec![Edit::Delete; a.len()]);
+        edits.extend(vec![Edit::Insert; b.len()]);
+        return;
+    }
+    // The path always fits in as many edits as there are lines
+    let snake: Snake = middle_snake(a, b, isize::MAX).unwrap();
+    diff_around(a, b, snake, edits);
+}
+
+// Add the edits before the middle snake, the equal lines on it, and the edits after it
+fn diff_around(a: &[String], b: &[String], snake: Snake, edits: &mut Vec<Edit>) {
+    if snake.edits <= 1 {
+        // At most one line was added or removed, so everything else lines up around it
+        let prefix: usize = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+        edits.extend(vec![Edit::Equal; prefix]);
+        if a.len() > b.len() {
+            edits.push(Edit::Delete);
+        } else if b.len() > a.len() {
+            edits.push(Edit::Insert);
+        }
+        edits.extend(vec![Edit::Equal; a.len().min(b.len()) - prefix]);
+        return;
+    }
+    let (x, y) = snake.start;
+    let (u, v) = snake.end;
+    diff_into(&a[..x], &b[..y], edits);
+    edits.extend(vec![Edit::Equal; u - x]);
+    diff_into(&a[u..], &b[v..], edits);
+}
+
+// Diff two files' lines and line them up side by side
+// Deleted and inserted lines next to each other are paired up into changed rows, the rest get a blank row opposite them
+pub fn align(a: &[String], b: &[String]) -> AlignedDiff {
+    // Lines shared by the start and end of both files never need diffing, which makes the common case (small edits) fast
+    let prefix: usize = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix: usize = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let a_middle: &[String] = &a[prefix..a.len() - suffix];
+    let b_middle: &[String] = &b[prefix..b.len() - suffix];
+    let mut edits: Vec<Edit> = vec![Edit::Equal; prefix];
+    match myers(a_middle, b_middle) {
+        Some(middle_edits) => edits.extend(middle_edits),
+        None => {
+            edits.extend(vec![Edit::Delete; a_middle.len()]);
+            edits.extend(vec![Edit::Insert; b_middle.len()]);
+        }
+    }
+    edits.extend(vec![Edit::Equal; suffix]);
+
+    let mut diff: AlignedDiff = AlignedDiff {
+        left: Vec::new(),
+        right: Vec::new(),
+        left_colors: Vec::new(),
+        right_colors: Vec::new(),
+        view: DiffView { hunks: Vec::new() },
+    };
+    let mut deleted: Vec<&String> = Vec::new();
+    let mut inserted: Vec<&String> = Vec::new();
+    let (mut i, mut j): (usize, usize) = (0, 0);
+    for edit in edits.iter().chain(std::iter::once(&Edit::Equal)) {
+        match edit {
+            Edit::Delete => {
+                deleted.push(&a[i]);
+                i += 1;
+            }
+            Edit::Insert => {
+                inserted.push(&b[j]);
+                j += 1;
+            }
+            Edit::Equal => {
+                if !deleted.is_empty() || !inserted.is_empty() {
+                    diff.view.hunks.push(diff.left.len() as i32);
+                    for row in 0..max(deleted.len(), inserted.len()) {
+                        let (left, right) = (deleted.get(row), inserted.get(row));
+                        let (left_color, right_color) = match (left, right) {
+                            (Some(_), Some(_)) => (CHANGED_COLOR, CHANGED_COLOR),
+                            (Some(_), None) => (DELETED_COLOR, 0),
+                            _ => (0, INSERTED_COLOR),
+                        };
+                        diff.left
+                            .push(left.map(|l| l.to_string()).unwrap_or_default());
+                        diff.right
+                            .push(right.map(|r| r.to_string()).unwrap_or_default());
+                        diff.left_colors.push(left_color);
+                        diff.right_colors.push(right_color);
+                    }
+                    deleted.clear();
+                    inserted.clear();
+                }
+                // The extra Equal chained on the end only flushes the last hunk, it isn't a real line
+                if i < a.len() && j < b.len() {
+                    diff.left.push(a[i].clone());
+                    diff.right.push(b[j].clone());
+                    diff.left_colors.push(0);
+                    diff.right_colors.push(0);
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+    }
+    diff
+}
@@ -0,0 +1,136 @@
+use std::{
+    env::VarError,
+    error::Error,
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+use shellexpand::full;
+
+use crate::browser::DirListing;
+
+// Why a file couldn't be opened, each shown as a message at the bottom of the screen
+#[derive(Debug)]
+pub enum LoadError {
+    // The path uses an environment variable that isn't set (or isn't valid unicode)
+    Expand {
+        path: String,
+        var: String,
+        cause: VarError,
+    },
+    NotFound(String),
+    PermissionDenied(String),
+    IsDirectory(String),
+    // Anything else that went wrong opening or reading it
+    Io(String, io::Error),
+}
+
+impl LoadError {
+    // Sort an io::Error from opening or reading a path into the error for it
+    pub fn from_io(path: &Path, error: io::Error) -> LoadError {
+        let path: String = path.display().to_string();
+        match error.kind() {
+            io::ErrorKind::NotFound => LoadError::NotFound(path),
+            io::ErrorKind::PermissionDenied => LoadError::PermissionDenied(path),
+            _ => LoadError::Io(path, error),
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Expand {
+                path,
+                var,
+                cause: VarError::NotPresent,
+            } => write!(f, "Could not expand \"{}\": ${} is not set", path, var),
+            LoadError::Expand { path, var, .. } => write!(
+                f,
+                "Could not expand \"{}\": ${} is not valid unicode",
+                path, var
+            ),
+            LoadError::NotFound(path) => write!(f, "\"{}\" does not exist", path),
+            LoadError::PermissionDenied(path) => {
+                write!(f, "Could not open \"{}\": permission denied", path)
+            }
+            LoadError::IsDirectory(path) => write!(f, "\"{}\" is a directory", path),
+            LoadError::Io(path, e) => write!(f, "Could not read \"{}\": {}", path, e),
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadError::Expand { cause, .. } => Some(cause),
+            LoadError::Io(_path, e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+// Expand tildes and environment variables in a path, ex: ~/$PROJECT/notes.txt
+pub fn expand_path(path: &str) -> Result<String, LoadError> {
+    full(path)
+        .map(|expanded| expanded.to_string())
+        .map_err(|e| LoadError::Expand {
+            path: path.to_string(),
+            var: e.var_name,
+            cause: e.cause,
+        })
+}
+
+// Opens the specified file (expands tildes and vars) and reads to a vector with a BufReader
+pub fn load_file(filename: &str) -> Result<Vec<String>, LoadError> {
+    read_lines(Path::new(&expand_path(filename)?))
+}
+
+// Read the lines of a file at a path that's already been expanded
+pub fn read_lines(path: &Path) -> Result<Vec<String>, LoadError> {
+    let file: File = File::open(path).map_err(|e| LoadError::from_io(path, e))?;
+    // Check the file that was opened rather than the path, which could have changed in between
+    let is_dir: bool = file
+        .metadata()
+        .map_err(|e| LoadError::from_io(path, e))?
+        .is_dir();
+    if is_dir {
+        return Err(LoadError::IsDirectory(path.display().to_string()));
+    }
+    read_all_lines(file).map_err(|e| LoadError::from_io(path, e))
+}
+
+// Read lines until the reader ends, dropping the \r of any \r\n line endings
+// Anything that isn't valid UTF-8 (ex: Latin-1 text) is shown as replacement characters instead of failing
+pub fn read_all_lines<R: Read>(reader: R) -> io::Result<Vec<String>> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in BufReader::new(reader).split(b'\n') {
+        let mut line: Vec<u8> = line?;
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        lines.push(String::from_utf8_lossy(&line).into_owned());
+    }
+    Ok(lines)
+}
+
+// What's at a path: a directory's listing, or a file's lines along with where it was read from once expanded
+pub enum PathContents {
+    Listing(DirListing),
+    Lines(PathBuf, Vec<String>),
+}
+
+// Read whatever is at a path, without showing it yet
+// Everything is read up front so that if it fails, whatever's on the screen can be left as it is
+pub fn read_path(filename: &str) -> Result<PathContents, LoadError> {
+    let expanded: PathBuf = PathBuf::from(expand_path(filename)?);
+    if expanded.is_dir() {
+        DirListing::read(&expanded)
+            .map(PathContents::Listing)
+            .map_err(|e| LoadError::from_io(&expanded, e))
+    } else {
+        read_lines(&expanded).map(|lines| PathContents::Lines(expanded, lines))
+    }
+}
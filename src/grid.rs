@@ -0,0 +1,120 @@
+use std::cmp::{max, min};
+
+use crate::backend::{self, Attributes};
+
+// One character on the screen, along with how it's drawn
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cell {
+    pub ch: char,
+    pub attributes: Attributes,
+    pub pair: i16,
+}
+
+pub const BLANK: Cell = Cell {
+    ch: ' ',
+    attributes: backend::A_NORMAL,
+    pair: 0,
+};
+
+// The contents of a screen kept in memory, drawn to the way curses draws to its windows
+// Backends without curses keep one of these and do their drawing on it
+pub struct Grid {
+    pub rows: i32,
+    pub cols: i32,
+    pub cells: Vec<Cell>,
+    pub cursor: (i32, i32),
+    attributes: Attributes,
+    pair: i16,
+}
+
+impl Grid {
+    pub fn new(rows: i32, cols: i32) -> Grid {
+        Grid {
+            rows,
+            cols,
+            cells: vec![BLANK; max(0, rows * cols) as usize],
+            cursor: (0, 0),
+            attributes: backend::A_NORMAL,
+            pair: 0,
+        }
+    }
+
+    pub fn cell(&self, y: i32, x: i32) -> Cell {
+        self.cells[(y * self.cols + x) as usize]
+    }
+
+    fn cell_mut(&mut self, y: i32, x: i32) -> Option<&mut Cell> {
+        if y >= 0 && y < self.rows && x >= 0 && x < self.cols {
+            Some(&mut self.cells[(y * self.cols + x) as usize])
+        } else {
+            None
+        }
+    }
+
+    // The text of a row, without the blanks at the end
+    pub fn row(&self, y: i32) -> String {
+        let start: usize = (y * self.cols) as usize;
+        let text: String = self.cells[start..start + self.cols as usize]
+            .iter()
+            .map(|c| c.ch)
+            .collect();
+        text.trim_end().to_string()
+    }
+
+    pub fn attrset(&mut self, attributes: Attributes, pair: i16) {
+        self.attributes = attributes;
+        self.pair = pair;
+    }
+
+    pub fn mv(&mut self, y: i32, x: i32) {
+        self.cursor = (y, x);
+    }
+
+    // Text that runs off the right edge is cut off rather than wrapped onto the next row
+    pub fn addnstr(&mut self, text: &str, n: usize) {
+        let (y, mut x) = self.cursor;
+        let (attributes, pair) = (self.attributes, self.pair);
+        for ch in text.chars().take(n) {
+            if let Some(cell) = self.cell_mut(y, x) {
+                *cell = Cell {
+                    ch,
+                    attributes,
+                    pair,
+                };
+            }
+            x += 1;
+        }
+        self.cursor = (y, min(x, self.cols));
+    }
+
+    pub fn clrtoeol(&mut self) {
+        let (y, x) = self.cursor;
+        for col in x..self.cols {
+            if let Some(cell) = self.cell_mut(y, col) {
+                *cell = BLANK;
+            }
+        }
+    }
+
+    // Like curses, a negative n changes everything up to the end of the row
+    pub fn mvchgat(&mut self, y: i32, x: i32, n: i32, attributes: Attributes, pair: i16) {
+        let end: i32 = if n < 0 {
+            self.cols
+        } else {
+            min(x + n, self.cols)
+        };
+        for col in x..end {
+            if let Some(cell) = self.cell_mut(y, col) {
+                cell.attributes = attributes;
+                cell.pair = pair;
+            }
+        }
+        self.cursor = (y, x);
+    }
+
+    pub fn erase(&mut self) {
+        for cell in self.cells.iter_mut() {
+            *cell = BLANK;
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    rc::Rc,
+};
+
+use crate::{
+    backend::{Attributes, Backend, Input, MouseEvent},
+    grid::{Cell, Grid},
+};
+
+struct Screen {
+    grid: Grid,
+    pairs: HashMap<i16, (i16, i16)>,
+    // Keys still to be read, with the mouse event each Input::KeyMouse is for, and None for each pause
+    keys: VecDeque<Option<(Input, Option<MouseEvent>)>>,
+    nodelay: bool,
+    mouse: Option<MouseEvent>,
+    mouse_enabled: bool,
+    beeps: usize,
+}
+
+// A screen kept in memory instead of a terminal, which reads its keys from a script
+// Clones share the same screen, so a test can keep one to queue keys and check what was drawn after least is done with the other
+// Once the script runs out, getch() returns None, which quits least the same as q
+// Ex:
+//     let screen = HeadlessBackend::new(24, 80);
+//     screen.keys("/error\nq");
+//     Pager::from_path("app.log").run_with(screen.clone());
+//     assert_eq!(screen.row(0), "...");
+#[derive(Clone)]
+pub struct HeadlessBackend {
+    screen: Rc<RefCell<Screen>>,
+}
+
+impl HeadlessBackend {
+    // A blank screen with the given number of rows and columns
+    pub fn new(rows: i32, cols: i32) -> HeadlessBackend {
+        HeadlessBackend {
+            screen: Rc::new(RefCell::new(Screen {
+                grid: Grid::new(rows, cols),
+                pairs: HashMap::new(),
+                keys: VecDeque::new(),
+                nodelay: false,
+                mouse: None,
+                mouse_enabled: false,
+                beeps: 0,
+            })),
+        }
+    }
+
+    // Queue up each char of a string as a key press (\n is Enter and \u{1b} is Esc)
+    pub fn keys(&self, keys: &str) {
+        for c in keys.chars() {
+            self.key(Input::Character(c));
+        }
+    }
+
+    // Queue up a key press, ex: Input::KeyDown
+    pub fn key(&self, input: Input) {
+        self.screen.borrow_mut().keys.push_back(Some((input, None)));
+    }
+
+    // Queue up a gap between keys, which is when least finds there's no key ready if it checks without waiting
+    // Ex: Esc, pause(), j is Esc followed by j, rather than Alt-j
+    pub fn pause(&self) {
+        self.screen.borrow_mut().keys.push_back(None);
+    }
+
+    // Queue up a mouse event, which only reaches least while it has mouse capture on
+    pub fn mouse(&self, event: MouseEvent) {
+        self.screen
+            .borrow_mut()
+            .keys
+            .push_back(Some((Input::KeyMouse, Some(event))));
+    }
+
+    // The text of a row, without the blanks at the end
+    pub fn row(&self, y: i32) -> String {
+        self.screen.borrow().grid.row(y)
+    }
+
+    // The text of every row, see row()
+    pub fn contents(&self) -> Vec<String> {
+        let rows: i32 = self.screen.borrow().grid.rows;
+        (0..rows).map(|y| self.row(y)).collect()
+    }
+
+    pub fn cell(&self, y: i32, x: i32) -> Cell {
+        self.screen.borrow().grid.cell(y, x)
+    }
+
+    // The foreground and background color a color pair was set up with
+    pub fn pair_colors(&self, pair: i16) -> Option<(i16, i16)> {
+        self.screen.borrow().pairs.get(&pair).copied()
+    }
+
+    // Where the cursor was left, as (row, column)
+    pub fn cursor(&self) -> (i32, i32) {
+        self.screen.borrow().grid.cursor
+    }
+
+    // How many keys are still to be read
+    pub fn pending_keys(&self) -> usize {
+        self.screen.borrow().keys.iter().flatten().count()
+    }
+
+    pub fn mouse_enabled(&self) -> bool {
+        self.screen.borrow().mouse_enabled
+    }
+
+    // How many times least has beeped
+    pub fn beeps(&self) -> usize {
+        self.screen.borrow().beeps
+    }
+}
+
+impl Backend for HeadlessBackend {
+    fn size(&self) -> (i32, i32) {
+        let screen = self.screen.borrow();
+        (screen.grid.rows, screen.grid.cols)
+    }
+
+    fn getch(&self) -> Option<Input> {
+        let mut screen = self.screen.borrow_mut();
+        loop {
+            match screen.keys.pop_front()? {
+                Some((input, mouse)) => {
+                    if mouse.is_some() {
+                        screen.mouse = mouse;
+                    }
+                    return Some(input);
+                }
+                // Waiting for a key just waits out the pause
+                None if screen.nodelay => return None,
+                None => (),
+            }
+        }
+    }
+
+    fn closed(&self) -> bool {
+        self.screen.borrow().keys.iter().all(|k| k.is_none())
+    }
+
+    fn ungetch(&self, input: &Input) {
+        let mut screen = self.screen.borrow_mut();
+        let mouse: Option<MouseEvent> = screen.mouse.filter(|_| *input == Input::KeyMouse);
+        screen.keys.push_front(Some((*input, mouse)));
+    }
+
+    fn nodelay(&self, enabled: bool) {
+        self.screen.borrow_mut().nodelay = enabled;
+    }
+
+    fn set_raw(&self, _enabled: bool) {}
+
+    fn set_mouse(&self, enabled: bool) {
+        self.screen.borrow_mut().mouse_enabled = enabled;
+    }
+
+    fn getmouse(&self) -> Option<MouseEvent> {
+        let screen = self.screen.borrow();
+        screen.mouse.filter(|_| screen.mouse_enabled)
+    }
+
+    fn set_bracketed_paste(&self, _enabled: bool) {}
+
+    fn init_pair(&self, pair: i16, foreground: i16, background: i16) {
+        self.screen
+            .borrow_mut()
+            .pairs
+            .insert(pair, (foreground, background));
+    }
+
+    fn attrset(&self, attributes: Attributes, pair: i16) {
+        self.screen.borrow_mut().grid.attrset(attributes, pair);
+    }
+
+    fn mv(&self, y: i32, x: i32) {
+        self.screen.borrow_mut().grid.mv(y, x);
+    }
+
+    fn addnstr(&self, text: &str, n: usize) {
+        self.screen.borrow_mut().grid.addnstr(text, n);
+    }
+
+    fn clrtoeol(&self) {
+        self.screen.borrow_mut().grid.clrtoeol();
+    }
+
+    fn mvchgat(&self, y: i32, x: i32, n: i32, attributes: Attributes, pair: i16) {
+        self.screen
+            .borrow_mut()
+            .grid
+            .mvchgat(y, x, n, attributes, pair);
+    }
+
+    fn erase(&self) {
+        self.screen.borrow_mut().grid.erase();
+    }
+
+    fn refresh(&self) {}
+
+    fn beep(&self) {
+        self.screen.borrow_mut().beeps += 1;
+    }
+
+    fn endwin(&self) {}
+}
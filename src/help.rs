@@ -9,7 +9,9 @@ pub const HELP_MESSAGE: &str = r#"
 
                       © 2020 Dylan DiGeronimo
 
-                Usage: least [-h, --help | filename]
+                Usage: least [-h, --help | filename | directory]
+                   [--no-mouse] [--wheel-lines N]
+                   least --diff file1 file2
 
                    Controls:
                        - q - Quit
@@ -20,8 +22,47 @@ pub const HELP_MESSAGE: &str = r#"
                        - g - Jump to top of file
                        - / - Forward search
                        - ? - Backward search
-                       - n - Next search result
+                       - n - Next search result from the top of the screen
                        - N - Last search result
-                       - o - Open a new file
+                       - Esc u - Clear search highlighting
+                       - Esc, Ctrl-C - Stop a running search
+                       - F - Search every open buffer
+                       - p - Pin a pattern to highlight
+                       - P - List and remove pins
+                       - o - Open a new file (Tab completes paths)
+                       - v - Edit file in $VISUAL/$EDITOR
+                       - m - Set mark at current line
+                       - | - Pipe screen, buffer or mark range to a command
+                       - s - Save screen, buffer or mark range to a file
+                       - [, ] - Previous/next open buffer
+                       - V - Visual line selection (y copy, s save, | pipe)
+                       - D - Diff buffer against a file
+                       - {, } - Previous/next diff hunk
+                       - J - Toggle JSON mode
+                       - z, Z - Fold/unfold JSON here, everywhere
+                       - t - Jump to a time in a log
+                       - e, E - Next/previous WARN or ERROR line
+                       - L - Hide log lines below a level
+                       - T - Toggle CSV/TSV table mode
+                       - Left, Right - Scroll table by a column
+                       - c - Search one column of a table
+                       - M - Toggle mouse capture
+                       - Wheel, click - Scroll, set current line
                        - h - Open help screen
-"#;
\ No newline at end of file
+
+                   Panes (Ctrl-W then):
+                       - s, v - Split stacked/side by side
+                       - w, W - Next/previous pane
+                       - c, q - Close pane
+                       - +, -, >, < - Resize pane
+
+                   Prompts:
+                       - Left, Right, Home, End - Move the cursor
+                       - Ctrl-W, Ctrl-U - Delete word, delete to start
+                       - Esc - Cancel
+
+                   Directory browser:
+                       - j, k, d, u, g - Move the selection
+                       - Enter - Open the selected entry
+                       - -, Backspace - Go up to the parent directory
+"#;
@@ -0,0 +1,509 @@
+use std::{cmp::min, collections::HashSet};
+
+// A parsed JSON value
+// Scalars and object keys are kept exactly as written (quotes, escapes and all) so they're shown the way the file has them,
+// and every value gets an id, so arrays and objects can be folded and any value can be found in the document
+#[derive(Clone)]
+pub enum Value {
+    Scalar(usize, String),
+    Array(usize, Vec<Value>),
+    Object(usize, Vec<(String, Value)>),
+}
+
+// A parsed JSON document, along with the (first, last) line of the text that each value (by id) was parsed from
+pub struct Document {
+    root: Value,
+    lines: Vec<(usize, usize)>,
+}
+
+// Deepest that arrays and objects can nest, parsing and rendering go one call deeper for each level
+// Anything nested deeper fails to parse (and is shown as text) rather than running out of stack
+const MAX_DEPTH: usize = 256;
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+    // How many arrays and objects the parser is inside of
+    depth: usize,
+    // Line that pos was on when line() last counted up to it
+    line: usize,
+    counted: usize,
+    // Lines of each value so far, by id
+    lines: Vec<(usize, usize)>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    // Line of the text that the current position is on, 0-based
+    // The parser only moves forward, so only the text since the last call needs counting
+    fn line(&mut self) -> usize {
+        let end: usize = min(self.pos, self.text.len());
+        self.line += self.text.as_bytes()[self.counted..end]
+            .iter()
+            .filter(|b| **b == b'\n')
+            .count();
+        self.counted = end;
+        self.line
+    }
+
+    // Give the value that starts at the current position an id
+    fn start_value(&mut self) -> usize {
+        let line: usize = self.line();
+        self.lines.push((line, line));
+        self.lines.len() - 1
+    }
+
+    // Describe a problem at the current position, with a 1-based line and column to find it by
+    fn error(&self, message: &str) -> String {
+        let mut end: usize = min(self.pos, self.text.len());
+        while !self.text.is_char_boundary(end) {
+            end -= 1;
+        }
+        let before: &str = &self.text[..end];
+        let line: usize = before.matches('\n').count() + 1;
+        let column: usize = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+        format!("{} at line {} column {}", message, line, column)
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{}'", byte as char)))
+        }
+    }
+
+    // Step inside an array or object, returning its id
+    fn enter(&mut self) -> Result<usize, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error(&format!("Nested more than {} levels deep", MAX_DEPTH)));
+        }
+        let id: usize = self.start_value();
+        self.depth += 1;
+        self.pos += 1;
+        Ok(id)
+    }
+
+    // Step past the ] or } that ends the array or object with the given id
+    fn leave(&mut self, id: usize) {
+        self.pos += 1;
+        self.depth -= 1;
+        self.lines[id].1 = self.line();
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') => self.object(),
+            Some(b'[') => self.array(),
+            _ => {
+                let id: usize = self.start_value();
+                self.scalar().map(|text| Value::Scalar(id, text))
+            }
+        }
+    }
+
+    // A string, number, true, false or null, as it's written
+    fn scalar(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some(b'"') => self.string(),
+            Some(b'-') | Some(b'0'..=b'9') => {
+                let start: usize = self.pos;
+                while let Some(b'0'..=b'9') | Some(b'-') | Some(b'+') | Some(b'.') | Some(b'e')
+                | Some(b'E') = self.peek()
+                {
+                    self.pos += 1;
+                }
+                Ok(self.text[start..self.pos].to_string())
+            }
+            _ => {
+                for literal in &["true", "false", "null"] {
+                    if self.text[self.pos..].starts_with(literal) {
+                        self.pos += literal.len();
+                        return Ok(literal.to_string());
+                    }
+                }
+                Err(self.error("Expected a value"))
+            }
+        }
+    }
+
+    // A string, including its quotes, with escapes left as they are
+    fn string(&mut self) -> Result<String, String> {
+        let start: usize = self.pos;
+        self.pos += 1;
+        loop {
+            match self.peek() {
+                Some(b'"') => break,
+                // An escape at the very end has nothing after it to skip
+                Some(b'\\') if self.pos + 1 < self.text.len() => self.pos += 2,
+                Some(b'\\') | None => return Err(self.error("Unterminated string")),
+                Some(_) => self.pos += 1,
+            }
+        }
+        self.pos += 1;
+        Ok(self.text[start..self.pos].to_string())
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        let id: usize = self.enter()?;
+        let mut items: Vec<Value> = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.leave(id);
+            return Ok(Value::Array(id, items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.leave(id);
+                    return Ok(Value::Array(id, items));
+                }
+                _ => return Err(self.error("Expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, String> {
+        let id: usize = self.enter()?;
+        let mut members: Vec<(String, Value)> = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.leave(id);
+            return Ok(Value::Object(id, members));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.error("Expected a key"));
+            }
+            let key: String = self.string()?;
+            self.expect(b':')?;
+            members.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.leave(id);
+                    return Ok(Value::Object(id, members));
+                }
+                _ => return Err(self.error("Expected ',' or '}'")),
+            }
+        }
+    }
+}
+
+// Parse a whole JSON document, the error says what went wrong and where
+pub fn parse(text: &str) -> Result<Document, String> {
+    let mut parser: Parser = Parser {
+        text,
+        pos: 0,
+        depth: 0,
+        line: 0,
+        counted: 0,
+        lines: Vec::new(),
+    };
+    let root: Value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.pos < text.len() {
+        return Err(parser.error("Unexpected text after the end of the JSON"));
+    }
+    Ok(Document {
+        root,
+        lines: parser.lines,
+    })
+}
+
+// The path to an object member, as .key when the key is a plain identifier and ["key"] otherwise
+fn member_path(path: &str, key: &str) -> String {
+    let inner: &str = &key[1..key.len() - 1];
+    let is_identifier: bool = inner
+        .chars()
+        .enumerate()
+        .all(|(i, c)| c == '_' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit()))
+        && !inner.is_empty();
+    if is_identifier {
+        format!("{}.{}", path, inner)
+    } else {
+        format!("{}[{}]", path, key)
+    }
+}
+
+// A JSON document pretty printed into lines, with some of its arrays and objects folded away into a single line
+// Keeps the original lines so the buffer can be switched back to them
+#[derive(Clone)]
+pub struct JsonView {
+    pub raw: Vec<String>,
+    root: Value,
+    // (first, last) line of raw that each value (by id) came from
+    value_lines: Vec<(usize, usize)>,
+    folded: HashSet<usize>,
+    // For each printed line, the JSON path of the value on it, the array or object that folding on it would fold,
+    // and the line of raw it came from
+    paths: Vec<String>,
+    containers: Vec<Option<usize>>,
+    sources: Vec<usize>,
+}
+
+impl JsonView {
+    // Constructor for a view with nothing folded, call render() to get its lines
+    pub fn new(raw: Vec<String>, document: Document) -> JsonView {
+        JsonView {
+            raw,
+            root: document.root,
+            value_lines: document.lines,
+            folded: HashSet::new(),
+            paths: Vec::new(),
+            containers: Vec::new(),
+            sources: Vec::new(),
+        }
+    }
+
+    // Pretty print the document with two space indents, skipping over the contents of folded arrays and objects
+    pub fn render(&mut self) -> Vec<String> {
+        let mut lines: Vec<String> = Vec::new();
+        self.paths.clear();
+        self.containers.clear();
+        self.sources.clear();
+        // Move the tree out while walking it, since rendering needs to record into self as it goes
+        let root: Value = std::mem::replace(&mut self.root, Value::Scalar(0, String::new()));
+        self.render_value(&root, 0, "", "", String::from("$"), None, &mut lines);
+        self.root = root;
+        lines
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render_value(
+        &mut self,
+        value: &Value,
+        depth: usize,
+        key: &str,
+        comma: &str,
+        path: String,
+        parent: Option<usize>,
+        lines: &mut Vec<String>,
+    ) {
+        let indent: String = "  ".repeat(depth);
+        let prefix: String = if key.is_empty() {
+            indent.clone()
+        } else {
+            format!("{}{}: ", indent, key)
+        };
+        let (id, open, close, len): (usize, char, char, usize) = match value {
+            Value::Scalar(id, text) => {
+                lines.push(format!("{}{}{}", prefix, text, comma));
+                self.paths.push(path);
+                self.containers.push(parent);
+                self.sources.push(self.value_lines[*id].0);
+                return;
+            }
+            Value::Array(id, items) => (*id, '[', ']', items.len()),
+            Value::Object(id, members) => (*id, '{', '}', members.len()),
+        };
+        if len == 0 || self.folded.contains(&id) {
+            let summary: String = match (len, open) {
+                (0, _) => String::new(),
+                (1, '[') => String::from(" ... 1 item "),
+                (1, _) => String::from(" ... 1 key "),
+                (n, '[') => format!(" ... {} items ", n),
+                (n, _) => format!(" ... {} keys ", n),
+            };
+            lines.push(format!("{}{}{}{}{}", prefix, open, summary, close, comma));
+            self.paths.push(path);
+            self.containers
+                .push(if len == 0 { parent } else { Some(id) });
+            self.sources.push(self.value_lines[id].0);
+            return;
+        }
+        lines.push(format!("{}{}", prefix, open));
+        self.paths.push(path.clone());
+        self.containers.push(Some(id));
+        self.sources.push(self.value_lines[id].0);
+        match value {
+            Value::Array(_, items) => {
+                for (i, item) in items.iter().enumerate() {
+                    let item_comma: &str = if i + 1 < len { "," } else { "" };
+                    let item_path: String = format!("{}[{}]", path, i);
+                    self.render_value(item, depth + 1, "", item_comma, item_path, Some(id), lines);
+                }
+            }
+            Value::Object(_, members) => {
+                for (i, (member_key, member)) in members.iter().enumerate() {
+                    let member_comma: &str = if i + 1 < len { "," } else { "" };
+                    let path: String = member_path(&path, member_key);
+                    self.render_value(
+                        member,
+                        depth + 1,
+                        member_key,
+                        member_comma,
+                        path,
+                        Some(id),
+                        lines,
+                    );
+                }
+            }
+            Value::Scalar(..) => (),
+        }
+        lines.push(format!("{}{}{}", indent, close, comma));
+        self.paths.push(path);
+        self.containers.push(Some(id));
+        self.sources.push(self.value_lines[id].1);
+    }
+
+    // Line of raw that a printed line came from, or the end of raw if it's past the last one
+    pub fn original_line(&self, line: i32) -> usize {
+        self.sources
+            .get(line as usize)
+            .copied()
+            .unwrap_or(self.raw.len())
+    }
+
+    // The first printed line that comes from a line of raw at or after the given one
+    pub fn shown_line(&self, original: usize) -> i32 {
+        self.sources.partition_point(|&l| l < original) as i32
+    }
+
+    // JSON path of the value on the given printed line
+    pub fn path(&self, line: i32) -> Option<&str> {
+        self.paths.get(line as usize).map(|p| p.as_str())
+    }
+
+    // Fold the array or object that starts on, ends on, or contains the given line, or unfold it if it's folded
+    // Returns the line it starts on once the view is rendered again, or None if there's nothing there to fold (ex: a lone number)
+    pub fn toggle_fold(&mut self, line: i32) -> Option<usize> {
+        let id: usize = (*self.containers.get(line as usize)?)?;
+        if !self.folded.remove(&id) {
+            self.folded.insert(id);
+        }
+        Some(id)
+    }
+
+    // Fold every array and object inside the top level one, or unfold everything if anything is already folded
+    pub fn toggle_fold_all(&mut self) {
+        if self.folded.is_empty() {
+            let mut ids: Vec<usize> = Vec::new();
+            collect_ids(&self.root, &mut ids);
+            // The top level is always the first id, leave it open so there's something to look at
+            self.folded.extend(ids.into_iter().skip(1));
+        } else {
+            self.folded.clear();
+        }
+    }
+
+    // The printed line the given array or object starts on, as of the last render()
+    pub fn start_line(&self, id: usize) -> Option<i32> {
+        self.containers
+            .iter()
+            .position(|c| *c == Some(id))
+            .map(|l| l as i32)
+    }
+}
+
+// Ids of every array and object in a value, outermost first
+fn collect_ids(value: &Value, ids: &mut Vec<usize>) {
+    match value {
+        Value::Scalar(..) => (),
+        Value::Array(id, items) => {
+            ids.push(*id);
+            items.iter().for_each(|item| collect_ids(item, ids));
+        }
+        Value::Object(id, members) => {
+            ids.push(*id);
+            members
+                .iter()
+                .for_each(|(_key, member)| collect_ids(member, ids));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The error from parsing text that isn't valid JSON
+    fn parse_error(text: &str) -> String {
+        match parse(text) {
+            Ok(_) => panic!("{:?} parsed", text),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn parses_nested_values() {
+        match parse(r#"{"a": [1, "two", null], "b": {}}"#).map(|d| d.root) {
+            Ok(Value::Object(_, members)) => {
+                assert_eq!(members.len(), 2);
+                assert_eq!(members[0].0, "\"a\"");
+            }
+            _ => panic!("expected an object"),
+        }
+    }
+
+    #[test]
+    fn rejects_unterminated_strings() {
+        assert_eq!(
+            parse_error(r#"["abc"#),
+            "Unterminated string at line 1 column 6"
+        );
+        assert_eq!(
+            parse_error(r#"["\"#),
+            "Unterminated string at line 1 column 3"
+        );
+        assert_eq!(
+            parse_error(r#"{"\"#),
+            "Unterminated string at line 1 column 3"
+        );
+        assert_eq!(
+            parse_error("[\"é\\"),
+            "Unterminated string at line 1 column 5"
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert_eq!(parse_error("[1,"), "Expected a value at line 1 column 4");
+        assert_eq!(
+            parse_error("[1 2]"),
+            "Expected ',' or ']' at line 1 column 4"
+        );
+        assert_eq!(
+            parse_error("{\n  1: 2}"),
+            "Expected a key at line 2 column 3"
+        );
+        assert_eq!(parse_error(r#"{"a" 1}"#), "Expected ':' at line 1 column 6");
+        assert_eq!(
+            parse_error("{} x"),
+            "Unexpected text after the end of the JSON at line 1 column 4"
+        );
+        assert_eq!(parse_error(""), "Expected a value at line 1 column 1");
+    }
+
+    #[test]
+    fn rejects_deep_nesting() {
+        let deepest: String = "[".repeat(MAX_DEPTH) + &"]".repeat(MAX_DEPTH);
+        assert!(parse(&deepest).is_ok());
+        let too_deep: String = "[".repeat(200_000);
+        assert_eq!(
+            parse_error(&too_deep),
+            format!(
+                "Nested more than {} levels deep at line 1 column {}",
+                MAX_DEPTH,
+                MAX_DEPTH + 1
+            )
+        );
+    }
+}
@@ -0,0 +1,47 @@
+// least as a library, so other programs can page their output with it without shelling out to the least binary
+// See Pager for how to start a session
+#[cfg(feature = "crossterm")]
+extern crate crossterm;
+#[cfg(feature = "pancurses")]
+extern crate pancurses;
+extern crate shellexpand;
+
+pub mod backend;
+mod browser;
+mod buffer;
+mod clipboard;
+mod complete;
+#[cfg(feature = "crossterm")]
+mod crossterm_backend;
+#[cfg(feature = "pancurses")]
+mod curses;
+mod diff;
+mod file;
+mod grid;
+mod headless;
+mod help;
+mod json;
+mod loglevel;
+mod pager;
+mod pane;
+mod pins;
+mod prompt;
+mod search;
+mod shell;
+mod table;
+mod timestamp;
+mod window;
+
+pub use crate::{
+    file::{load_file, LoadError},
+    grid::Cell,
+    headless::HeadlessBackend,
+    help::HELP_MESSAGE,
+    pager::Pager,
+    search::search_scraper,
+};
+
+#[cfg(feature = "crossterm")]
+pub use crate::crossterm_backend::CrosstermBackend;
+#[cfg(feature = "pancurses")]
+pub use crate::curses::CursesBackend;
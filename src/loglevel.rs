@@ -0,0 +1,205 @@
+// Color pairs used for log lines, set up in WindowState::start()
+pub const DEBUG_COLOR: i16 = 6;
+pub const WARN_COLOR: i16 = 7;
+pub const ERROR_COLOR: i16 = 8;
+pub const FATAL_COLOR: i16 = 9;
+
+// Only this much of the start of a line is looked at for its level, so a long message that mentions ERROR doesn't count
+const LEVEL_SEARCH_LEN: usize = 200;
+
+// Log levels, from least to most severe
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub enum Level {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl Level {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Level::Trace => "TRACE",
+            Level::Debug => "DEBUG",
+            Level::Info => "INFO",
+            Level::Warn => "WARN",
+            Level::Error => "ERROR",
+            Level::Fatal => "FATAL",
+        }
+    }
+
+    // Color pair to draw lines of this level in, 0 for the normal color
+    fn color(&self) -> i16 {
+        match self {
+            Level::Trace | Level::Debug => DEBUG_COLOR,
+            Level::Info => 0,
+            Level::Warn => WARN_COLOR,
+            Level::Error => ERROR_COLOR,
+            Level::Fatal => FATAL_COLOR,
+        }
+    }
+}
+
+// The level a word names, ignoring case, including common abbreviations and aliases
+fn level_from_name(name: &str) -> Option<Level> {
+    match name.to_ascii_uppercase().as_str() {
+        "TRACE" => Some(Level::Trace),
+        "DEBUG" => Some(Level::Debug),
+        "INFO" => Some(Level::Info),
+        "WARN" | "WARNING" => Some(Level::Warn),
+        "ERROR" | "ERR" => Some(Level::Error),
+        "FATAL" | "CRITICAL" | "CRIT" | "PANIC" => Some(Level::Fatal),
+        _ => None,
+    }
+}
+
+// The level of a numeric JSON level field, as used by pino and bunyan (10 trace, 20 debug ... 60 fatal)
+fn level_from_number(number: &str) -> Option<Level> {
+    match number.parse::<u32>().ok()? {
+        0..=19 => Some(Level::Trace),
+        20..=29 => Some(Level::Debug),
+        30..=39 => Some(Level::Info),
+        40..=49 => Some(Level::Warn),
+        50..=59 => Some(Level::Error),
+        _ => Some(Level::Fatal),
+    }
+}
+
+// Find the level of a log line along with the byte range of the word that says it
+// Matches a JSON "level" field, an all caps level word (ex: ERROR), or a lower case one where it's clearly a level (ex: [warn], level=info)
+pub fn detect(line: &str) -> Option<(Level, usize, usize)> {
+    let mut head_len: usize = line.len().min(LEVEL_SEARCH_LEN);
+    while !line.is_char_boundary(head_len) {
+        head_len -= 1;
+    }
+    let head: &str = &line[..head_len];
+    for key in &["\"level\":", "\"severity\":", "\"lvl\":"] {
+        if let Some(key_index) = head.find(key) {
+            let after_key: usize = key_index + key.len();
+            let value_start: usize =
+                after_key + head[after_key..].len() - head[after_key..].trim_start().len();
+            let value: &str = &head[value_start..];
+            if let Some(quoted) = value.strip_prefix('"') {
+                let end: usize = quoted.find('"').unwrap_or(quoted.len());
+                if let Some(level) = level_from_name(&quoted[..end]) {
+                    return Some((level, value_start + 1, value_start + 1 + end));
+                }
+            } else {
+                let end: usize = value.bytes().take_while(|b| b.is_ascii_digit()).count();
+                if let Some(level) = level_from_number(&value[..end]) {
+                    return Some((level, value_start, value_start + end));
+                }
+            }
+        }
+    }
+    let bytes: &[u8] = head.as_bytes();
+    let mut start: usize = 0;
+    while start < bytes.len() {
+        if !bytes[start].is_ascii_alphabetic() {
+            start += 1;
+            continue;
+        }
+        let end: usize = start
+            + bytes[start..]
+                .iter()
+                .take_while(|b| b.is_ascii_alphabetic())
+                .count();
+        let part_of_word =
+            |b: Option<&u8>| b.is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'_');
+        let whole_word: bool = !part_of_word(start.checked_sub(1).and_then(|i| bytes.get(i)))
+            && !part_of_word(bytes.get(end));
+        let word: &str = &head[start..end];
+        if whole_word {
+            if let Some(level) = level_from_name(word) {
+                let before: &str = &head[..start];
+                let all_caps: bool = word.bytes().all(|b| b.is_ascii_uppercase());
+                let marked: bool = before.ends_with('[')
+                    || before.ends_with('<')
+                    || before.ends_with("level=")
+                    || before.ends_with("lvl=");
+                if all_caps || marked {
+                    return Some((level, start, end));
+                }
+            }
+        }
+        start = end;
+    }
+    None
+}
+
+// Whether lines look like a log: at least a fifth of the first ones have a level
+pub fn is_log(lines: &[String]) -> bool {
+    let sample: &[String] = &lines[..lines.len().min(200)];
+    let with_level: usize = sample.iter().filter(|l| detect(l).is_some()).count();
+    with_level > 0 && with_level * 5 >= sample.len()
+}
+
+// A log's lines with the level each one belongs to, and which of them are shown when hiding lines below a level
+// Lines without a level of their own (ex: a stack trace) belong to the level of the line above them
+// Keeps the original lines so the filter can be changed or removed
+#[derive(Clone)]
+pub struct LogView {
+    pub raw: Vec<String>,
+    levels: Vec<Option<Level>>,
+    pub min_level: Option<Level>,
+    // Indexes into raw of the lines being shown
+    shown: Vec<usize>,
+}
+
+impl LogView {
+    // Constructor for a view showing every line
+    pub fn new(raw: Vec<String>) -> LogView {
+        let mut levels: Vec<Option<Level>> = Vec::with_capacity(raw.len());
+        let mut current: Option<Level> = None;
+        for line in &raw {
+            if let Some((level, _start, _end)) = detect(line) {
+                current = Some(level);
+            }
+            levels.push(current);
+        }
+        let shown: Vec<usize> = (0..raw.len()).collect();
+        LogView {
+            raw,
+            levels,
+            min_level: None,
+            shown,
+        }
+    }
+
+    // Hide the lines below a level (or show all of them with None), returning the lines left along with their colors
+    // Lines before the first one with a level count as the lowest level
+    pub fn filter(&mut self, min_level: Option<Level>) -> (Vec<String>, Vec<i16>) {
+        self.min_level = min_level;
+        self.shown = (0..self.raw.len())
+            .filter(|&i| match min_level {
+                Some(min_level) => self.levels[i].unwrap_or(Level::Trace) >= min_level,
+                None => true,
+            })
+            .collect();
+        let lines: Vec<String> = self.shown.iter().map(|&i| self.raw[i].clone()).collect();
+        (lines, self.colors())
+    }
+
+    // Color pair of every line being shown, for line_colors
+    pub fn colors(&self) -> Vec<i16> {
+        self.shown
+            .iter()
+            .map(|&i| self.levels[i].map_or(0, |l| l.color()))
+            .collect()
+    }
+
+    // Index into raw of a line being shown, or the end of raw if it's past the last one
+    pub fn original_line(&self, line: i32) -> usize {
+        self.shown
+            .get(line as usize)
+            .copied()
+            .unwrap_or(self.raw.len())
+    }
+
+    // The first line being shown at or after an index into raw
+    pub fn shown_line(&self, original: usize) -> i32 {
+        self.shown.partition_point(|&i| i < original) as i32
+    }
+}
@@ -0,0 +1,115 @@
+use std::{
+    cmp::max,
+    io::{self, Read},
+};
+
+#[cfg(feature = "crossterm")]
+use crate::crossterm_backend::CrosstermBackend;
+#[cfg(all(feature = "pancurses", not(feature = "crossterm")))]
+use crate::curses::CursesBackend;
+use crate::{
+    backend::Backend,
+    file::{load_file, read_all_lines},
+    window::{self, WindowState},
+};
+
+// What a pager shows when it starts
+enum Content {
+    Lines(Vec<String>),
+    Path(String),
+    Diff(String, String),
+}
+
+// An interactive least session, with everything least can do (search, panes, JSON and table modes, etc)
+// Ex: Pager::from_lines(lines).name("build output").mouse(false).run()
+pub struct Pager {
+    content: Content,
+    name: String,
+    mouse_enabled: bool,
+    wheel_lines: i32,
+}
+
+impl Pager {
+    fn with_content(content: Content) -> Pager {
+        Pager {
+            content,
+            name: String::new(),
+            mouse_enabled: true,
+            wheel_lines: 3,
+        }
+    }
+
+    // Page the given lines
+    pub fn from_lines(lines: Vec<String>) -> Pager {
+        Pager::with_content(Content::Lines(lines))
+    }
+
+    // Page everything that can be read from a reader (ex: a child process's output) until it ends
+    // Anything that isn't valid UTF-8 is shown as replacement characters instead of failing
+    pub fn from_reader<R: Read>(reader: R) -> io::Result<Pager> {
+        Ok(Pager::from_lines(read_all_lines(reader)?))
+    }
+
+    // Page a file (or browse a directory) the same way least <path> does, tildes and variables in the path are expanded
+    pub fn from_path(path: &str) -> Pager {
+        Pager::with_content(Content::Path(path.to_string()))
+    }
+
+    // Show the differences between two files side by side, the same way least --diff does
+    pub fn from_diff(left: &str, right: &str) -> Pager {
+        Pager::with_content(Content::Diff(left.to_string(), right.to_string()))
+    }
+
+    // Name to show for lines given with from_lines() or from_reader(), ex: in pane titles
+    pub fn name(self, name: &str) -> Pager {
+        let mut new_pager: Pager = self;
+        new_pager.name = name.to_string();
+        new_pager
+    }
+
+    // Whether least captures the mouse (on by default), see --no-mouse
+    pub fn mouse(self, enabled: bool) -> Pager {
+        let mut new_pager: Pager = self;
+        new_pager.mouse_enabled = enabled;
+        new_pager
+    }
+
+    // How many lines one step of the mouse wheel scrolls (3 by default), see --wheel-lines
+    pub fn wheel_lines(self, lines: i32) -> Pager {
+        let mut new_pager: Pager = self;
+        new_pager.wheel_lines = max(1, lines);
+        new_pager
+    }
+
+    // Take over the terminal and page until the user quits with q
+    // The terminal is drawn with crossterm if least is built with the crossterm feature, otherwise with pancurses
+    #[cfg(feature = "crossterm")]
+    pub fn run(self) {
+        self.run_with(CrosstermBackend::new());
+    }
+
+    #[cfg(all(feature = "pancurses", not(feature = "crossterm")))]
+    pub fn run(self) {
+        self.run_with(CursesBackend::new());
+    }
+
+    // Page on another screen than the terminal, ex: a HeadlessBackend fed a script of keys in a test
+    pub fn run_with(self, backend: impl Backend + 'static) {
+        let state: WindowState =
+            WindowState::start(Box::new(backend), self.mouse_enabled, self.wheel_lines);
+        let state: WindowState = match self.content {
+            Content::Lines(lines) => state.open_lines(self.name, lines),
+            Content::Path(path) => state.open_path(&path),
+            Content::Diff(left, right) => match (load_file(&left), load_file(&right)) {
+                (Ok(left_lines), Ok(right_lines)) => {
+                    state.open_diff(left, &left_lines, right, &right_lines)
+                }
+                (Err(e), _) | (_, Err(e)) => {
+                    state.show_message(&e.to_string());
+                    state
+                }
+            },
+        };
+        window::run(state);
+    }
+}
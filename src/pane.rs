@@ -0,0 +1,276 @@
+use std::cmp::{max, min};
+
+use crate::{
+    backend::{self, Backend},
+    buffer::Buffer,
+};
+
+// A rectangle of the screen, in rows and columns
+#[derive(Clone, Copy)]
+pub struct Rect {
+    pub top: i32,
+    pub left: i32,
+    pub height: i32,
+    pub width: i32,
+}
+
+// A pane that isn't focused, along with the buffer it's showing and where it was last laid out
+// The focused pane's buffer lives directly in WindowState, the same way the current buffer always has
+pub struct Pane {
+    pub id: usize,
+    pub buffer: Buffer,
+    pub rect: Rect,
+}
+
+// How the screen is divided between panes: either a single pane, or a split into two smaller layouts
+// A vertical split puts its halves side by side, with a one column separator, a horizontal split stacks them
+pub enum Layout {
+    Pane(usize),
+    Split {
+        vertical: bool,
+        // Percentage of the space given to the first half
+        ratio: i32,
+        first: Box<Layout>,
+        second: Box<Layout>,
+    },
+}
+
+impl Layout {
+    // Work out the rectangle of every pane when the layout fills the given area, in the same order as pane_ids()
+    pub fn rects(&self, area: Rect, out: &mut Vec<(usize, Rect)>) {
+        match self {
+            Layout::Pane(id) => out.push((*id, area)),
+            Layout::Split {
+                vertical,
+                ratio,
+                first,
+                second,
+            } => {
+                let (first_area, second_area) = split_area(area, *vertical, *ratio);
+                first.rects(first_area, out);
+                second.rects(second_area, out);
+            }
+        }
+    }
+
+    // Work out the column and rows of every separator between side by side panes, as (column, top row, height)
+    pub fn separators(&self, area: Rect, out: &mut Vec<(i32, i32, i32)>) {
+        if let Layout::Split {
+            vertical,
+            ratio,
+            first,
+            second,
+        } = self
+        {
+            let (first_area, second_area) = split_area(area, *vertical, *ratio);
+            if *vertical {
+                out.push((first_area.left + first_area.width, area.top, area.height));
+            }
+            first.separators(first_area, out);
+            second.separators(second_area, out);
+        }
+    }
+
+    // Ids of every pane, from top left to bottom right, aka the order Ctrl-W w moves focus in
+    pub fn pane_ids(&self) -> Vec<usize> {
+        match self {
+            Layout::Pane(id) => vec![*id],
+            Layout::Split { first, second, .. } => {
+                let mut ids: Vec<usize> = first.pane_ids();
+                ids.extend(second.pane_ids());
+                ids
+            }
+        }
+    }
+
+    // Replace the pane with the given id by an even split between it and a new pane
+    pub fn split(&mut self, id: usize, new_id: usize, vertical: bool) {
+        match self {
+            Layout::Pane(pane_id) if *pane_id == id => {
+                *self = Layout::Split {
+                    vertical,
+                    ratio: 50,
+                    first: Box::new(Layout::Pane(id)),
+                    second: Box::new(Layout::Pane(new_id)),
+                };
+            }
+            Layout::Pane(_) => (),
+            Layout::Split { first, second, .. } => {
+                first.split(id, new_id, vertical);
+                second.split(id, new_id, vertical);
+            }
+        }
+    }
+
+    // Remove the pane with the given id, giving its space to whatever it was split from
+    // Returns false if it's the only pane, which can't be removed
+    pub fn remove(&mut self, id: usize) -> bool {
+        if let Layout::Split { first, second, .. } = self {
+            let remaining: Option<Layout> = match (first.as_ref(), second.as_ref()) {
+                (Layout::Pane(first_id), _) if *first_id == id => {
+                    Some(std::mem::replace(second.as_mut(), Layout::Pane(id)))
+                }
+                (_, Layout::Pane(second_id)) if *second_id == id => {
+                    Some(std::mem::replace(first.as_mut(), Layout::Pane(id)))
+                }
+                _ => None,
+            };
+            match remaining {
+                Some(remaining) => {
+                    *self = remaining;
+                    true
+                }
+                None => first.remove(id) || second.remove(id),
+            }
+        } else {
+            false
+        }
+    }
+
+    // Grow (or shrink, with a negative amount) the pane with the given id by changing the ratio of the
+    // closest split around it in the given direction
+    // Returns false if there's no such split, ex: making a pane wider when there are no side by side panes
+    pub fn resize(&mut self, id: usize, vertical: bool, amount: i32) -> bool {
+        if let Layout::Split {
+            vertical: split_vertical,
+            ratio,
+            first,
+            second,
+        } = self
+        {
+            if first.resize(id, vertical, amount) || second.resize(id, vertical, amount) {
+                return true;
+            }
+            if *split_vertical == vertical {
+                if first.pane_ids().contains(&id) {
+                    *ratio = (*ratio + amount).clamp(10, 90);
+                    return true;
+                } else if second.pane_ids().contains(&id) {
+                    *ratio = (*ratio - amount).clamp(10, 90);
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+// Divide an area in two according to a split's direction and ratio, leaving room for the separator of a vertical split
+fn split_area(area: Rect, vertical: bool, ratio: i32) -> (Rect, Rect) {
+    if vertical {
+        let first_width: i32 = max(1, (area.width - 1) * ratio / 100);
+        (
+            Rect {
+                width: first_width,
+                ..area
+            },
+            Rect {
+                left: area.left + first_width + 1,
+                width: max(1, area.width - first_width - 1),
+                ..area
+            },
+        )
+    } else {
+        let first_height: i32 = max(1, area.height * ratio / 100);
+        (
+            Rect {
+                height: first_height,
+                ..area
+            },
+            Rect {
+                top: area.top + first_height,
+                height: max(1, area.height - first_height),
+                ..area
+            },
+        )
+    }
+}
+
+// Expand tabs to spaces (up to the next multiple of 8 columns), so a line takes up exactly as many columns as it has chars
+pub fn expand_tabs(line: &str) -> String {
+    let mut expanded: String = String::with_capacity(line.len());
+    let mut col: usize = 0;
+    for c in line.chars() {
+        if c == '\t' {
+            let spaces: usize = 8 - col % 8;
+            expanded.extend(std::iter::repeat_n(' ', spaces));
+            col += spaces;
+        } else {
+            expanded.push(c);
+            col += 1;
+        }
+    }
+    expanded
+}
+
+// The screen column that the given byte offset into a line is drawn at, once tabs are expanded
+pub fn display_col(line: &str, byte_index: usize) -> i32 {
+    expand_tabs(&line[..byte_index]).chars().count() as i32
+}
+
+// Color pairs for search results, set up in WindowState::start()
+pub const MATCH_COLOR: i16 = 2;
+pub const CURRENT_MATCH_COLOR: i16 = 16;
+
+// Fill a rectangle of the screen with lines, starting from line number top
+// Lines are cut off at the edge of the rectangle, and rows past the end of the lines are blanked
+// Lines with a color pair in colors (other than 0) have their whole row drawn in it
+pub fn draw_rows(window: &dyn Backend, lines: &[String], colors: &[i16], top: i32, rect: &Rect) {
+    let blank: String = " ".repeat(max(0, rect.width) as usize);
+    for row in 0..rect.height {
+        window.mvaddstr(rect.top + row, rect.left, &blank);
+        if let Some(line) = lines.get((top + row) as usize) {
+            let visible: String = expand_tabs(line)
+                .chars()
+                .take(max(0, rect.width) as usize)
+                .collect();
+            window.mvaddstr(rect.top + row, rect.left, &visible);
+            if let Some(&pair) = colors.get((top + row) as usize).filter(|p| **p > 0) {
+                window.mvchgat(
+                    rect.top + row,
+                    rect.left,
+                    rect.width,
+                    backend::A_NORMAL,
+                    pair,
+                );
+            }
+        }
+    }
+}
+
+// Highlight the search results in the lines drawn in a rectangle of the screen, starting from line number top
+// The results are in line order, and the current one (the one last jumped to) gets its own color so it stands out
+pub fn draw_matches(
+    window: &dyn Backend,
+    results: &[(i32, i32, i32)],
+    current: Option<usize>,
+    lines: &[String],
+    top: i32,
+    rect: &Rect,
+) {
+    let first: usize = results.partition_point(|r| r.0 < top);
+    for (index, &(line_number, start, end)) in results.iter().enumerate().skip(first) {
+        if line_number >= top + rect.height {
+            break;
+        }
+        let line: &String = match lines.get(line_number as usize) {
+            Some(line) => line,
+            None => break,
+        };
+        let start_col: i32 = display_col(line, start as usize);
+        let end_col: i32 = min(display_col(line, end as usize), rect.width);
+        if start_col < end_col {
+            window.mvchgat(
+                rect.top + line_number - top,
+                rect.left + start_col,
+                end_col - start_col,
+                backend::A_NORMAL,
+                if current == Some(index) {
+                    CURRENT_MATCH_COLOR
+                } else {
+                    MATCH_COLOR
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use std::cmp::min;
+
+use crate::{
+    backend::{self, Backend},
+    pane::{display_col, Rect},
+};
+
+// Color pairs for pinned patterns (set up in WindowState::start()), with the background color of each and the name it's listed by
+pub const PIN_COLORS: &[(i16, i16, &str)] = &[
+    (10, backend::COLOR_YELLOW, "yellow"),
+    (11, backend::COLOR_CYAN, "cyan"),
+    (12, backend::COLOR_MAGENTA, "magenta"),
+    (13, backend::COLOR_BLUE, "blue"),
+    (14, backend::COLOR_RED, "red"),
+    (15, backend::COLOR_WHITE, "white"),
+];
+
+// A pattern that stays highlighted in its own color, in every pane, until it's removed
+pub struct Pin {
+    pub pattern: String,
+    // Index into PIN_COLORS
+    pub color: usize,
+}
+
+// The first color that isn't already used by a pin, if there are any left
+pub fn free_color(pins: &[Pin]) -> Option<usize> {
+    (0..PIN_COLORS.len()).find(|c| pins.iter().all(|p| p.color != *c))
+}
+
+// Highlight every match of every pin in the lines drawn in a rectangle of the screen, starting from line number top
+// Pins are drawn in the order they were added, so where matches overlap the newest pin's color is the one that shows
+pub fn draw_pins(window: &dyn Backend, pins: &[Pin], lines: &[String], top: i32, rect: &Rect) {
+    for row in 0..rect.height {
+        let line: &String = match lines.get((top + row) as usize) {
+            Some(line) => line,
+            None => break,
+        };
+        for pin in pins {
+            for (start, _match) in line.match_indices(pin.pattern.as_str()) {
+                let start_col: i32 = display_col(line, start);
+                let end_col: i32 = min(display_col(line, start + pin.pattern.len()), rect.width);
+                if start_col < end_col {
+                    window.mvchgat(
+                        rect.top + row,
+                        rect.left + start_col,
+                        end_col - start_col,
+                        backend::A_NORMAL,
+                        PIN_COLORS[pin.color].0,
+                    );
+                }
+            }
+        }
+    }
+}
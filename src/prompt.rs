@@ -0,0 +1,120 @@
+use std::cmp::min;
+
+use crate::backend::Input;
+
+// Editable single line of input, shared by every prompt (open file, search, etc)
+// Tracks the text as chars so that the cursor and scrolling work on multibyte input
+pub struct LineEditor {
+    chars: Vec<char>,
+    cursor: usize,
+    scroll: usize,
+}
+
+impl LineEditor {
+    pub fn new() -> LineEditor {
+        LineEditor {
+            chars: Vec::new(),
+            cursor: 0,
+            scroll: 0,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    // Replace the whole input (ex: with a tab completion), leaving the cursor at the end
+    pub fn set_text(&mut self, text: &str) {
+        self.chars = text.chars().collect();
+        self.cursor = self.chars.len();
+    }
+
+    pub fn insert_str(&mut self, text: &str) {
+        for c in text.chars() {
+            self.chars.insert(self.cursor, c);
+            self.cursor += 1;
+        }
+    }
+
+    // Apply an editing key to the input
+    // Returns false if the key isn't one the editor knows about, so the caller can handle it
+    pub fn handle(&mut self, input: &Input) -> bool {
+        match input {
+            Input::KeyLeft | Input::Character('\u{2}') => {
+                self.cursor = self.cursor.saturating_sub(1);
+            }
+            Input::KeyRight | Input::Character('\u{6}') => {
+                self.cursor = min(self.cursor + 1, self.chars.len());
+            }
+            Input::KeyHome | Input::Character('\u{1}') => {
+                self.cursor = 0;
+            }
+            Input::KeyEnd | Input::Character('\u{5}') => {
+                self.cursor = self.chars.len();
+            }
+            // Pancurses doesn't detect backspace on all platforms as KeyBackspace, so catch the raw char codes too
+            Input::KeyBackspace
+            | Input::Character('\u{7f}')
+            | Input::Character('\u{8}')
+            | Input::Character('\u{8f}') => {
+                if self.cursor > 0 {
+                    self.cursor -= 1;
+                    self.chars.remove(self.cursor);
+                }
+            }
+            Input::KeyDC | Input::Character('\u{4}') => {
+                if self.cursor < self.chars.len() {
+                    self.chars.remove(self.cursor);
+                }
+            }
+            // Ctrl-W - Delete the word before the cursor, along with any whitespace between it and the cursor
+            Input::Character('\u{17}') => {
+                let mut start: usize = self.cursor;
+                while start > 0 && self.chars[start - 1].is_whitespace() {
+                    start -= 1;
+                }
+                while start > 0 && !self.chars[start - 1].is_whitespace() {
+                    start -= 1;
+                }
+                self.chars.drain(start..self.cursor);
+                self.cursor = start;
+            }
+            // Ctrl-U - Delete everything before the cursor
+            Input::Character('\u{15}') => {
+                self.chars.drain(..self.cursor);
+                self.cursor = 0;
+            }
+            Input::Character(c) if !c.is_control() => {
+                self.chars.insert(self.cursor, *c);
+                self.cursor += 1;
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    // Get the part of the input that fits in a box of the given width, and the cursor's column within it
+    // The view scrolls horizontally to follow the cursor, and a leading "<" marks input hidden off the left edge
+    pub fn view(&mut self, width: usize) -> (String, usize) {
+        if width < 2 {
+            return (String::new(), 0);
+        }
+        // Don't leave empty space at the end of the box if text has been deleted while scrolled
+        if self.chars.len() + 1 < self.scroll + width {
+            self.scroll = (self.chars.len() + 1).saturating_sub(width);
+        }
+        if self.cursor > self.scroll + width - 1 {
+            self.scroll = self.cursor + 1 - width;
+        }
+        // When scrolled, the first column is taken up by the marker, so the cursor has to stay right of it
+        if self.scroll > 0 && self.cursor < self.scroll + 1 {
+            self.scroll = self.cursor.saturating_sub(1);
+        }
+        let end: usize = min(self.chars.len(), self.scroll + width);
+        let mut visible: String = self.chars[self.scroll..end].iter().collect();
+        if self.scroll > 0 {
+            visible.replace_range(..visible.chars().next().map_or(0, |c| c.len_utf8()), "<");
+        }
+        (visible, self.cursor - self.scroll)
+    }
+}
@@ -0,0 +1,229 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, Receiver},
+        Arc,
+    },
+    thread,
+};
+
+// How many lines a worker searches between reporting back, small enough for the progress to move and cancelling to be quick
+const CHUNK_LINES: usize = 10000;
+
+// Given a vector of strings crawl over it and search for any occurences
+// Returns a vector of tuples corresponding to the (line number, start character number, end character number)
+pub fn search_scraper(lines: &[String], search_term: &str) -> Vec<(i32, i32, i32)> {
+    let mut results: Vec<(i32, i32, i32)> = Vec::new();
+    for (line_number, line) in lines.iter().enumerate() {
+        let line_number = line_number as i32;
+        if line.contains(search_term) {
+            // Get a vec of tuples of (starting index of substring, substring)
+            let line_result_tuples: Vec<(usize, &str)> = line.match_indices(search_term).collect();
+            // Filter that vec into just the starting indices and put them into a tuple with the line number and the ending index of the substring
+            for t in line_result_tuples {
+                let end_index: i32 = (t.0 + search_term.len()) as i32;
+                results.append(&mut vec![(line_number, t.0 as i32, end_index)]);
+            }
+        }
+    }
+    results
+}
+
+// A run of lines searched by a worker, with the matches found in them (in line order, numbered from the start of the file)
+pub struct Chunk {
+    // Position of the chunk in the order the lines are searched in, see SearchJob
+    pub index: usize,
+    pub results: Vec<(i32, i32, i32)>,
+    pub lines: usize,
+}
+
+// A search running on worker threads (one per core), which send back a Chunk at a time until every line has been searched
+// The lines are split into chunks going outward from a starting line in the direction of the search, wrapping around the
+// end of the file, and the workers take them in that order, so the nearest matches are found first
+// Chunks can finish out of order, so the first match to jump to is in the first chunk with any once all the ones before it are in
+pub struct SearchJob {
+    pub chunks: Receiver<Chunk>,
+    pub chunk_count: usize,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl SearchJob {
+    // Start searching lines for a term, from the start line down (or up if reverse is true)
+    pub fn spawn(lines: Arc<Vec<String>>, term: String, start: usize, reverse: bool) -> SearchJob {
+        let (sender, chunks) = mpsc::channel();
+        let cancelled: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        let ranges: Arc<Vec<(usize, usize)>> = Arc::new(chunk_ranges(lines.len(), start, reverse));
+        let chunk_count: usize = ranges.len();
+        let next_chunk: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+        let term: Arc<String> = Arc::new(term);
+        let workers: usize = thread::available_parallelism().map_or(1, |n| n.get());
+        for _worker in 0..workers.min(chunk_count) {
+            let lines: Arc<Vec<String>> = Arc::clone(&lines);
+            let term: Arc<String> = Arc::clone(&term);
+            let ranges: Arc<Vec<(usize, usize)>> = Arc::clone(&ranges);
+            let next_chunk: Arc<AtomicUsize> = Arc::clone(&next_chunk);
+            let cancelled: Arc<AtomicBool> = Arc::clone(&cancelled);
+            let sender = sender.clone();
+            thread::spawn(move || loop {
+                let index: usize = next_chunk.fetch_add(1, Ordering::Relaxed);
+                let (from, to) = match ranges.get(index) {
+                    Some(range) if !cancelled.load(Ordering::Relaxed) => *range,
+                    _ => return,
+                };
+                let results: Vec<(i32, i32, i32)> = search_scraper(&lines[from..to], &term)
+                    .into_iter()
+                    .map(|(line, start, end)| (line + from as i32, start, end))
+                    .collect();
+                let chunk: Chunk = Chunk {
+                    index,
+                    results,
+                    lines: to - from,
+                };
+                if sender.send(chunk).is_err() {
+                    return;
+                }
+            });
+        }
+        SearchJob {
+            chunks,
+            chunk_count,
+            cancelled,
+        }
+    }
+
+    // Tell the workers to stop at the end of the chunks they're on
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for SearchJob {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+// The [from, to) ranges of lines to search, in the order to search them
+// Forwards that's the start line to the end of the file and then the top of the file to the start line,
+// backwards it's the start line up to the top and then the end of the file up to the start line
+fn chunk_ranges(len: usize, start: usize, reverse: bool) -> Vec<(usize, usize)> {
+    let start: usize = start.min(len);
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    if reverse {
+        let split: usize = (start + 1).min(len);
+        for (low, high) in &[(0, split), (split, len)] {
+            let mut to: usize = *high;
+            while to > *low {
+                let from: usize = to.saturating_sub(CHUNK_LINES).max(*low);
+                ranges.push((from, to));
+                to = from;
+            }
+        }
+    } else {
+        for (low, high) in &[(start, len), (0, start)] {
+            let mut from: usize = *low;
+            while from < *high {
+                let to: usize = (from + CHUNK_LINES).min(*high);
+                ranges.push((from, to));
+                from = to;
+            }
+        }
+    }
+    ranges
+}
+
+// Where a buffer searched by a global search is, so its matches can be opened later
+#[derive(Clone, Copy, PartialEq)]
+pub enum Source {
+    // Showing in another pane, by the pane's id
+    Pane(usize),
+    // Open in the background, by its index in WindowState::buffers
+    Background(usize),
+}
+
+// One buffer's matches from a global search
+pub struct FileMatches {
+    pub source: Source,
+    pub name: String,
+    pub lines: Arc<Vec<String>>,
+    pub results: Vec<(i32, i32, i32)>,
+}
+
+// A buffer in the results of a global search, with its matches
+#[derive(Clone)]
+pub struct ListedBuffer {
+    pub source: Source,
+    pub name: String,
+    pub results: Vec<(i32, i32, i32)>,
+}
+
+// The results of a search across every open buffer, listed grouped by buffer: a line with the buffer's name,
+// followed by each line of it with a match, numbered and indented (ex: "    12: the matching line")
+#[derive(Clone)]
+pub struct MatchList {
+    pub term: String,
+    files: Vec<ListedBuffer>,
+    // For every listed line after the title, the index into files and the line it opens (a name opens its first match)
+    rows: Vec<(usize, i32)>,
+}
+
+impl MatchList {
+    // Build the list along with its lines, and where the term is in them (as search results, so they get highlighted)
+    pub fn new(
+        term: String,
+        files: Vec<FileMatches>,
+    ) -> (MatchList, Vec<String>, Vec<(i32, i32, i32)>) {
+        let total: usize = files.iter().map(|f| f.results.len()).sum();
+        let mut lines: Vec<String> = vec![format!(
+            "\"{}\": {} in {}",
+            term,
+            count(total, "match", "matches"),
+            count(files.len(), "buffer", "buffers")
+        )];
+        let mut highlights: Vec<(i32, i32, i32)> = Vec::new();
+        let mut rows: Vec<(usize, i32)> = Vec::new();
+        for (file, matches) in files.iter().enumerate() {
+            lines.push(format!(
+                "{} ({})",
+                matches.name,
+                count(matches.results.len(), "match", "matches")
+            ));
+            rows.push((file, matches.results[0].0));
+            let mut previous_line: Option<i32> = None;
+            for &(line, start, end) in &matches.results {
+                let prefix: String = format!("{:>6}: ", line + 1);
+                // A line with more than one match is listed once, with all of them highlighted
+                if previous_line != Some(line) {
+                    lines.push(format!("{}{}", prefix, matches.lines[line as usize]));
+                    rows.push((file, line));
+                    previous_line = Some(line);
+                }
+                highlights.push((
+                    lines.len() as i32 - 1,
+                    (prefix.len() as i32) + start,
+                    (prefix.len() as i32) + end,
+                ));
+            }
+        }
+        let files: Vec<ListedBuffer> = files
+            .into_iter()
+            .map(|f| ListedBuffer {
+                source: f.source,
+                name: f.name,
+                results: f.results,
+            })
+            .collect();
+        (MatchList { term, files, rows }, lines, highlights)
+    }
+
+    // Where the given listed line points: the buffer and the line to open it at
+    pub fn target(&self, row: i32) -> Option<(&ListedBuffer, i32)> {
+        let (file, line) = *self.rows.get((row - 1) as usize)?;
+        Some((&self.files[file], line))
+    }
+}
+
+// A number with the singular or plural of what it's counting, ex: "1 match" or "3 matches"
+fn count(n: usize, singular: &str, plural: &str) -> String {
+    format!("{} {}", n, if n == 1 { singular } else { plural })
+}
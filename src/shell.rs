@@ -0,0 +1,89 @@
+use std::{
+    env,
+    io::{self, Write},
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+};
+
+// What a shell command printed, split into lines, along with how it exited
+pub struct CommandOutput {
+    pub stdout: Vec<String>,
+    pub stderr: Vec<String>,
+    pub success: bool,
+    pub status: String,
+}
+
+// Run a command through sh with the given lines as its stdin, and collect everything it prints
+pub fn pipe_lines(command: &str, lines: &[String]) -> io::Result<CommandOutput> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // Feed stdin from another thread, otherwise a command that prints as it reads can fill its stdout pipe and deadlock us
+    let mut stdin = child.stdin.take().expect("Child stdin was not piped");
+    let input: String = lines.iter().map(|l| format!("{}\n", l)).collect();
+    let writer = thread::spawn(move || {
+        // The command is allowed to exit without reading all of its input (ex: head), so a broken pipe isn't an error
+        stdin.write_all(input.as_bytes()).ok();
+    });
+    let output = child.wait_with_output()?;
+    writer.join().ok();
+    Ok(CommandOutput {
+        stdout: String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(String::from)
+            .collect(),
+        stderr: String::from_utf8_lossy(&output.stderr)
+            .lines()
+            .map(String::from)
+            .collect(),
+        success: output.status.success(),
+        status: output.status.to_string(),
+    })
+}
+
+// Editors known to take a "+N" argument to open at line N
+const PLUS_LINE_EDITORS: &[&str] = &[
+    "vi",
+    "vim",
+    "nvim",
+    "view",
+    "gvim",
+    "emacs",
+    "emacsclient",
+    "nano",
+    "pico",
+    "micro",
+    "joe",
+    "jed",
+    "kak",
+    "mg",
+    "ne",
+];
+
+// Build the command for the user's editor ($VISUAL, then $EDITOR, then vi) to open a file at the given (1-based) line
+// The editor variable can include arguments, ex: EDITOR="emacsclient -t"
+pub fn editor_command(path: &Path, line: i32) -> Command {
+    let editor: String = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| String::from("vi"));
+    let mut words = editor.split_whitespace();
+    let program: &str = words.next().unwrap_or("vi");
+    let mut command = Command::new(program);
+    command.args(words);
+    let program_name: &str = Path::new(program)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(program);
+    if PLUS_LINE_EDITORS.contains(&program_name) {
+        command.arg(format!("+{}", line));
+    }
+    command.arg(path);
+    command
+}
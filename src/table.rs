@@ -0,0 +1,404 @@
+use std::{
+    cmp::{max, min},
+    mem,
+};
+
+// Delimiters that detect_delimiter() tries, in order of preference when more than one fits
+const DELIMITERS: &[char] = &[',', '\t', ';', '|'];
+
+// Columns wider than this are cut off, so one long cell doesn't push every other column off the screen
+const MAX_COLUMN_WIDTH: usize = 40;
+
+// What goes between columns
+const SEPARATOR: &str = " | ";
+
+// Split delimited text into records of fields, following the CSV conventions (RFC 4180):
+// fields can be wrapped in double quotes to contain the delimiter, newlines or "" (for a literal quote)
+// Each record comes with the line of the text it starts on, since a quoted newline makes a record span more than one
+pub fn parse_records(text: &str, delimiter: char) -> Vec<(usize, Vec<String>)> {
+    let mut records: Vec<(usize, Vec<String>)> = Vec::new();
+    let mut record: Vec<String> = Vec::new();
+    let mut field: String = String::new();
+    let mut in_quotes: bool = false;
+    let (mut line, mut record_line): (usize, usize) = (0, 0);
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\n' {
+            line += 1;
+        }
+        if in_quotes {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    field.push('"');
+                    chars.next();
+                } else {
+                    in_quotes = false;
+                }
+            } else {
+                field.push(c);
+            }
+        } else if c == '"' && field.is_empty() {
+            in_quotes = true;
+        } else if c == delimiter {
+            record.push(mem::take(&mut field));
+        } else if c == '\n' {
+            record.push(mem::take(&mut field));
+            records.push((record_line, mem::take(&mut record)));
+            record_line = line;
+        } else if c != '\r' {
+            field.push(c);
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((record_line, record));
+    }
+    // Blank lines (usually just one at the end of the file) aren't records
+    records.retain(|(_line, r)| !(r.len() == 1 && r[0].is_empty()));
+    records
+}
+
+// Guess which delimiter the lines use: the one that splits the first lines into the same number of fields (more than one),
+// preferring whichever gives the most fields
+pub fn detect_delimiter(lines: &[String]) -> Option<char> {
+    let sample_len: usize = min(lines.len(), 50);
+    let sample: String = lines[..sample_len].join("\n");
+    let mut best: Option<(usize, char)> = None;
+    for &delimiter in DELIMITERS {
+        let mut records: Vec<Vec<String>> = parse_records(&sample, delimiter)
+            .into_iter()
+            .map(|(_line, record)| record)
+            .collect();
+        // The sample can cut a record with a quoted newline in half, so leave the last one out when there's more to the file
+        if sample_len < lines.len() {
+            records.pop();
+        }
+        let fields: usize = records.first().map_or(0, |r| r.len());
+        if fields > 1
+            && records.iter().all(|r| r.len() == fields)
+            && best.is_none_or(|(most, _)| fields > most)
+        {
+            best = Some((fields, delimiter));
+        }
+    }
+    best.map(|(_fields, delimiter)| delimiter)
+}
+
+// Delimited records drawn as a table, with every column padded to the width of its widest cell
+// The first record is the header, which is drawn separately so it stays at the top while the rest scroll
+// Keeps the original lines so the buffer can be switched back to them
+#[derive(Clone)]
+pub struct Table {
+    pub raw: Vec<String>,
+    pub delimiter: char,
+    records: Vec<Vec<String>>,
+    // Line of raw that each record starts on
+    record_lines: Vec<usize>,
+    widths: Vec<usize>,
+    // Leftmost column on the screen, columns before it are scrolled off to the left
+    pub first_column: usize,
+    // The last search of a single column, kept apart from where its matches land on the screen so they can be found
+    // again after scrolling
+    column_search: Option<ColumnSearch>,
+}
+
+// The column that was searched, and every match in its fields as (line, start, end)
+#[derive(Clone)]
+struct ColumnSearch {
+    column: usize,
+    matches: Vec<(usize, usize, usize)>,
+}
+
+impl Table {
+    // Parse the lines into a table, None if there's no header to make one from
+    pub fn new(raw: Vec<String>, delimiter: char) -> Option<Table> {
+        let (record_lines, records): (Vec<usize>, Vec<Vec<String>>) =
+            parse_records(&raw.join("\n"), delimiter)
+                .into_iter()
+                .unzip();
+        if records.is_empty() {
+            return None;
+        }
+        let column_count: usize = records.iter().map(|r| r.len()).max().unwrap_or(0);
+        let mut widths: Vec<usize> = vec![1; column_count];
+        for record in &records {
+            for (col, field) in record.iter().enumerate() {
+                widths[col] =
+                    widths[col].max(min(cell_text(field).chars().count(), MAX_COLUMN_WIDTH));
+            }
+        }
+        Some(Table {
+            raw,
+            delimiter,
+            records,
+            record_lines,
+            widths,
+            first_column: 0,
+            column_search: None,
+        })
+    }
+
+    pub fn column_count(&self) -> usize {
+        self.widths.len()
+    }
+
+    // Name of a column from the header, or its number if the header doesn't have one
+    pub fn column_name(&self, col: usize) -> String {
+        match self.records[0].get(col) {
+            Some(name) if !name.is_empty() => cell_text(name),
+            _ => format!("column {}", col + 1),
+        }
+    }
+
+    // Find a column by its name in the header (ignoring case) or by its 1-based number
+    pub fn find_column(&self, name: &str) -> Option<usize> {
+        let name: &str = name.trim();
+        self.records[0]
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(name))
+            .or_else(|| {
+                name.parse::<usize>()
+                    .ok()
+                    .filter(|n| *n >= 1 && *n <= self.column_count())
+                    .map(|n| n - 1)
+            })
+    }
+
+    // A cell as it's shown: cut off at the column's width, and padded out to it
+    fn padded_cell(&self, record: usize, col: usize) -> String {
+        let width: usize = self.widths[col];
+        let text: String = self.records[record]
+            .get(col)
+            .map(|f| cell_text(f))
+            .unwrap_or_default();
+        if text.chars().count() > width {
+            let mut cut: String = text.chars().take(width - 1).collect();
+            cut.push('~');
+            cut
+        } else {
+            format!("{:<width$}", text, width = width)
+        }
+    }
+
+    // A record as a line of the table, from the first column on the screen
+    fn render_record(&self, record: usize) -> String {
+        (self.first_column..self.column_count())
+            .map(|col| self.padded_cell(record, col))
+            .collect::<Vec<String>>()
+            .join(SEPARATOR)
+    }
+
+    // The header as a line of the table
+    pub fn header(&self) -> String {
+        self.render_record(0)
+    }
+
+    // Every record but the header as lines of the table, line n is record n + 1
+    pub fn render(&self) -> Vec<String> {
+        (1..self.records.len())
+            .map(|record| self.render_record(record))
+            .collect()
+    }
+
+    // Line of raw that a line of the table starts on, or the end of raw if it's past the last one
+    pub fn original_line(&self, line: i32) -> usize {
+        self.record_lines
+            .get(line as usize + 1)
+            .copied()
+            .unwrap_or(self.raw.len())
+    }
+
+    // The first line of the table whose record starts at or after a line of raw
+    // The header's line (and anything before it) is the first line of the table
+    pub fn shown_line(&self, original: usize) -> i32 {
+        max(
+            0,
+            self.record_lines.partition_point(|&l| l < original) as i32 - 1,
+        )
+    }
+
+    // Byte offset that the given column starts at in a line of the table, if it were rendered starting from column from
+    fn byte_offset(&self, line: usize, from: usize, col: usize) -> usize {
+        (from..col)
+            .map(|c| self.padded_cell(line + 1, c).len() + SEPARATOR.len())
+            .sum()
+    }
+
+    // Screen column that the given column starts at, or None if it's scrolled off to the left
+    pub fn screen_offset(&self, col: usize) -> Option<usize> {
+        if col < self.first_column {
+            return None;
+        }
+        Some(
+            (self.first_column..col)
+                .map(|c| self.widths[c] + SEPARATOR.len())
+                .sum(),
+        )
+    }
+
+    // Find every occurrence of a term in one column, returned as (line, start, end) on the screen like search_scraper() returns
+    // The whole of each field is searched, even the part cut off on the screen, see column_search_results()
+    pub fn search_column(&mut self, col: usize, term: &str) -> Vec<(i32, i32, i32)> {
+        let mut matches: Vec<(usize, usize, usize)> = Vec::new();
+        if !term.is_empty() {
+            for line in 0..self.records.len() - 1 {
+                let text: String = self.field_text(line, col);
+                for (start, _match) in text.match_indices(term) {
+                    matches.push((line, start, start + term.len()));
+                }
+            }
+        }
+        self.column_search = Some(ColumnSearch {
+            column: col,
+            matches,
+        });
+        self.column_search_results()
+    }
+
+    // Forget the last column search, ex: once another search has replaced it
+    pub fn end_column_search(&mut self) {
+        self.column_search = None;
+    }
+
+    // A field of a line of the table as it's shown, before it's cut off at the column's width
+    fn field_text(&self, line: usize, col: usize) -> String {
+        self.records[line + 1]
+            .get(col)
+            .map(|f| cell_text(f))
+            .unwrap_or_default()
+    }
+
+    // Where the matches of the last column search are on the screen, none if the column is scrolled off to the left
+    // The highlights stop at the column's width, and a match that's entirely cut off highlights the ~ that marks where
+    // the field was cut
+    fn column_search_results(&self) -> Vec<(i32, i32, i32)> {
+        let (col, matches) = match &self.column_search {
+            Some(search) if search.column >= self.first_column => (search.column, &search.matches),
+            _ => return Vec::new(),
+        };
+        let width: usize = self.widths[col];
+        matches
+            .iter()
+            .map(|&(line, start, end)| {
+                let text: String = self.field_text(line, col);
+                // Bytes of the field that are shown before the ~, or all of them if it fits
+                let shown: usize = if text.chars().count() > width {
+                    text.char_indices().nth(width - 1).map_or(0, |(i, _c)| i)
+                } else {
+                    text.len()
+                };
+                let (start, end): (usize, usize) = if start < shown {
+                    (start, min(end, shown))
+                } else {
+                    (shown, shown + 1)
+                };
+                let offset: usize = self.byte_offset(line, self.first_column, col);
+                (line as i32, (offset + start) as i32, (offset + end) as i32)
+            })
+            .collect()
+    }
+
+    // Scroll to make a different column the first one on the screen
+    // Matches of a column search are found again from the fields they're in, other search results are moved along with
+    // the text they point at, and dropped if it's now scrolled off to the left
+    pub fn scroll_to(&mut self, first_column: usize, search_results: &mut Vec<(i32, i32, i32)>) {
+        if self.column_search.is_some() {
+            self.first_column = first_column;
+            *search_results = self.column_search_results();
+            return;
+        }
+        let shifted: Vec<(i32, i32, i32)> = search_results
+            .iter()
+            .filter_map(|&(line, start, end)| {
+                let line_start: usize = self.byte_offset(line as usize, 0, self.first_column);
+                let new_line_start: usize = self.byte_offset(line as usize, 0, first_column);
+                (line_start + start as usize)
+                    .checked_sub(new_line_start)
+                    .map(|s| (line, s as i32, s as i32 + end - start))
+            })
+            .collect();
+        self.first_column = first_column;
+        *search_results = shifted;
+    }
+}
+
+// A field with anything that would break up the row (newlines, tabs) made visible on a single line
+fn cell_text(field: &str) -> String {
+    field.replace('\n', "\\n").replace('\t', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Just the fields of each record
+    fn fields(text: &str, delimiter: char) -> Vec<Vec<String>> {
+        parse_records(text, delimiter)
+            .into_iter()
+            .map(|(_line, record)| record)
+            .collect()
+    }
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn quoted_fields_can_contain_the_delimiter() {
+        assert_eq!(
+            fields("name,place\nada,\"London, UK\"", ','),
+            vec![vec!["name", "place"], vec!["ada", "London, UK"]]
+        );
+    }
+
+    #[test]
+    fn quoted_fields_can_contain_newlines() {
+        let table: Table = Table::new(lines("name,note\nada,\"two\nlines\"\nbob,x"), ',').unwrap();
+        assert_eq!(table.records[1], vec!["ada", "two\nlines"]);
+        assert_eq!(table.records[2], vec!["bob", "x"]);
+        // bob's record starts on the line after the one ada's ends on
+        assert_eq!(table.record_lines, vec![0, 1, 3]);
+        assert_eq!(table.original_line(1), 3);
+        assert_eq!(table.shown_line(2), 1);
+    }
+
+    #[test]
+    fn doubled_quotes_are_literal_quotes() {
+        assert_eq!(
+            fields("a,b\n\"say \"\"hi\"\"\",x", ','),
+            vec![vec!["a", "b"], vec!["say \"hi\"", "x"]]
+        );
+    }
+
+    #[test]
+    fn blank_lines_at_the_end_are_not_records() {
+        assert_eq!(
+            parse_records("a\tb\n1\t2\n\n", '\t'),
+            vec![
+                (0, vec![String::from("a"), String::from("b")]),
+                (1, vec![String::from("1"), String::from("2")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn detects_the_delimiter_when_the_sample_cuts_a_quoted_record() {
+        // The 50 line sample ends halfway through the last record, which only has its second field on line 51
+        let mut text: Vec<String> = vec![String::from("a;b")];
+        text.extend((0..48).map(|n| format!("{};x", n)));
+        text.push(String::from("\"two"));
+        text.push(String::from("lines\";y"));
+        assert_eq!(detect_delimiter(&text), Some(';'));
+        let table: Table = Table::new(text, ';').unwrap();
+        assert_eq!(table.records[49], vec!["two\nlines", "y"]);
+    }
+
+    #[test]
+    fn prefers_the_delimiter_that_splits_into_the_most_columns() {
+        assert_eq!(detect_delimiter(&lines("a,b;c,d\n1,2;3,4")), Some(','));
+        assert_eq!(
+            detect_delimiter(&lines("just text\nnothing to split")),
+            None
+        );
+    }
+}
@@ -0,0 +1,426 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MONTHS: &[&str] = &[
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+// A point in time read from a log line
+// Times without a UTC offset are taken as they are, as if they were UTC, so they still compare correctly with each other
+#[derive(Clone, Copy)]
+pub struct Timestamp {
+    // Seconds since 1970-01-01 00:00:00 UTC
+    pub seconds: i64,
+    // Offset from UTC in seconds, if the time had one
+    pub offset: Option<i64>,
+}
+
+impl Timestamp {
+    // Format as YYYY-MM-DD HH:MM:SS in the time's own offset, followed by the offset if it has one
+    pub fn display(&self) -> String {
+        let local: i64 = self.seconds + self.offset.unwrap_or(0);
+        let (year, month, day) = civil_from_days(local.div_euclid(86400));
+        let secs_of_day: i64 = local.rem_euclid(86400);
+        let time: String = format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            year,
+            month,
+            day,
+            secs_of_day / 3600,
+            (secs_of_day % 3600) / 60,
+            secs_of_day % 60
+        );
+        match self.offset {
+            Some(offset) => format!(
+                "{} {}{:02}:{:02}",
+                time,
+                if offset < 0 { '-' } else { '+' },
+                offset.abs() / 3600,
+                (offset.abs() % 3600) / 60
+            ),
+            None => time,
+        }
+    }
+}
+
+// Convert a count of days since 1970-01-01 into a (year, month, day) date
+// See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+pub fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z: i64 = days + 719468;
+    let era: i64 = z.div_euclid(146097);
+    let doe: i64 = z.rem_euclid(146097);
+    let yoe: i64 = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy: i64 = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp: i64 = (5 * doy + 2) / 153;
+    let day: i64 = doy - (153 * mp + 2) / 5 + 1;
+    let month: i64 = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year: i64 = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+// Convert a (year, month, day) date into a count of days since 1970-01-01, the inverse of civil_from_days()
+// See http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y: i64 = if month <= 2 { year - 1 } else { year };
+    let era: i64 = y.div_euclid(400);
+    let yoe: i64 = y.rem_euclid(400);
+    let mp: i64 = if month > 2 { month - 3 } else { month + 9 };
+    let doy: i64 = (153 * mp + 2) / 5 + day - 1;
+    let doe: i64 = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+// The year it is now, used for syslog times, which leave the year out
+fn current_year() -> i64 {
+    let now: i64 = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    civil_from_days(now.div_euclid(86400)).0
+}
+
+// Reads numbers and fixed characters off the front of a string, for picking apart a timestamp
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(text: &'a str) -> Scanner<'a> {
+        Scanner {
+            bytes: text.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    // Exactly n digits
+    fn digits(&mut self, n: usize) -> Option<i64> {
+        let digits: &[u8] = self.bytes.get(self.pos..self.pos + n)?;
+        if !digits.iter().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        self.pos += n;
+        Some(digits.iter().fold(0, |acc, b| acc * 10 + (b - b'0') as i64))
+    }
+
+    fn byte(&mut self, expected: u8) -> Option<()> {
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    // A byte that may or may not be there, returns whether it was
+    fn optional(&mut self, expected: u8) -> bool {
+        self.byte(expected).is_some()
+    }
+
+    // Three letter month name
+    fn month(&mut self) -> Option<i64> {
+        let name: &[u8] = self.bytes.get(self.pos..self.pos + 3)?;
+        let month: usize = MONTHS.iter().position(|m| m.as_bytes() == name)?;
+        self.pos += 3;
+        Some(month as i64 + 1)
+    }
+
+    // Skip fractions of a second, they don't matter for finding lines
+    fn skip_fraction(&mut self) {
+        if self.peek() == Some(b'.') || self.peek() == Some(b',') {
+            self.pos += 1;
+            while self.peek().is_some_and(|b| b.is_ascii_digit()) {
+                self.pos += 1;
+            }
+        }
+    }
+
+    // HH:MM:SS, or HH:MM if seconds are optional
+    fn time(&mut self, seconds_optional: bool) -> Option<i64> {
+        let hours: i64 = self.digits(2)?;
+        self.byte(b':')?;
+        let minutes: i64 = self.digits(2)?;
+        let seconds: i64 = if self.optional(b':') {
+            self.digits(2)?
+        } else if seconds_optional {
+            0
+        } else {
+            return None;
+        };
+        if hours > 23 || minutes > 59 || seconds > 60 {
+            return None;
+        }
+        self.skip_fraction();
+        Some(hours * 3600 + minutes * 60 + seconds)
+    }
+
+    // Z, +HH:MM, +HHMM or +HH, returned in seconds east of UTC
+    fn offset(&mut self) -> Option<i64> {
+        if self.optional(b'Z') {
+            return Some(0);
+        }
+        let sign: i64 = match self.peek() {
+            Some(b'+') => 1,
+            Some(b'-') => -1,
+            _ => return None,
+        };
+        let start: usize = self.pos;
+        self.pos += 1;
+        let hours: Option<i64> = self.digits(2);
+        let hours: i64 = match hours {
+            Some(hours) => hours,
+            None => {
+                self.pos = start;
+                return None;
+            }
+        };
+        self.optional(b':');
+        let minutes: i64 = self.digits(2).unwrap_or(0);
+        Some(sign * (hours * 3600 + minutes * 60))
+    }
+
+    // True if the timestamp isn't just the start of a longer word or number
+    fn at_boundary(&self) -> bool {
+        self.peek().is_none_or(|b| !b.is_ascii_alphanumeric())
+    }
+}
+
+fn date(year: i64, month: i64, day: i64) -> Option<i64> {
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    Some(days_from_civil(year, month, day) * 86400)
+}
+
+fn with_offset(local: i64, offset: Option<i64>) -> Timestamp {
+    Timestamp {
+        seconds: local - offset.unwrap_or(0),
+        offset,
+    }
+}
+
+// ISO 8601, ex: 2020-10-11T22:14:15.003Z or 2020-10-11 22:14:15 +02:00
+fn iso_8601(text: &str, seconds_optional: bool) -> Option<Timestamp> {
+    let mut s: Scanner = Scanner::new(text);
+    let year: i64 = s.digits(4)?;
+    s.byte(b'-')?;
+    let month: i64 = s.digits(2)?;
+    s.byte(b'-')?;
+    let day: i64 = s.digits(2)?;
+    if !s.optional(b'T') {
+        s.byte(b' ')?;
+    }
+    let time: i64 = s.time(seconds_optional)?;
+    let offset: Option<i64> = {
+        let before_space: usize = s.pos;
+        s.optional(b' ');
+        let offset: Option<i64> = s.offset();
+        if offset.is_none() {
+            s.pos = before_space;
+        }
+        offset
+    };
+    if !s.at_boundary() {
+        return None;
+    }
+    Some(with_offset(date(year, month, day)? + time, offset))
+}
+
+// Syslog (RFC 3164), ex: Oct 11 22:14:15, which has no year so it's taken to be this year (see current_year())
+// That's wrong for lines from last year, ex: a log from December opened in January puts those lines in the future,
+// after January's, so the binary search for a time in jump to time no longer finds lines in order
+fn syslog(text: &str) -> Option<Timestamp> {
+    let mut s: Scanner = Scanner::new(text);
+    let month: i64 = s.month()?;
+    s.byte(b' ')?;
+    let day: i64 = if s.optional(b' ') {
+        s.digits(1)?
+    } else {
+        s.digits(2).or_else(|| s.digits(1))?
+    };
+    s.byte(b' ')?;
+    let time: i64 = s.time(false)?;
+    if !s.at_boundary() {
+        return None;
+    }
+    Some(with_offset(date(current_year(), month, day)? + time, None))
+}
+
+// Seconds (or milliseconds) since 1970, ex: 1602454455 or 1602454455123
+// Only ten or thirteen digit numbers starting with 1 count (2001 to 2033), so ordinary numbers aren't taken for times
+fn epoch(text: &str) -> Option<Timestamp> {
+    let digits: usize = text.bytes().take_while(|b| b.is_ascii_digit()).count();
+    if !text.starts_with('1') {
+        return None;
+    }
+    let mut s: Scanner = Scanner::new(text);
+    let seconds: i64 = match digits {
+        10 => s.digits(10)?,
+        13 => s.digits(13)? / 1000,
+        _ => return None,
+    };
+    s.skip_fraction();
+    if !s.at_boundary() {
+        return None;
+    }
+    Some(with_offset(seconds, Some(0)))
+}
+
+// Nginx and Apache access logs, ex: 10/Oct/2020:13:55:36 -0700
+fn nginx(text: &str) -> Option<Timestamp> {
+    let mut s: Scanner = Scanner::new(text);
+    let day: i64 = s.digits(2)?;
+    s.byte(b'/')?;
+    let month: i64 = s.month()?;
+    s.byte(b'/')?;
+    let year: i64 = s.digits(4)?;
+    s.byte(b':')?;
+    let time: i64 = s.time(false)?;
+    s.optional(b' ');
+    let offset: Option<i64> = s.offset();
+    Some(with_offset(date(year, month, day)? + time, offset))
+}
+
+// Find the timestamp at the start of a line (possibly in square brackets),
+// or for access logs, the bracketed one after the client address
+pub fn parse_line(line: &str) -> Option<Timestamp> {
+    let start: &str = line.trim_start();
+    let start: &str = start.strip_prefix('[').unwrap_or(start);
+    iso_8601(start, false)
+        .or_else(|| syslog(start))
+        .or_else(|| epoch(start))
+        .or_else(|| nginx(start))
+        .or_else(|| {
+            let (bracket, _c) = line.char_indices().take(80).find(|(_i, c)| *c == '[')?;
+            nginx(&line[bracket + 1..])
+        })
+}
+
+// Read a time typed into the jump to time prompt
+// As well as anything parse_line() understands, takes YYYY-MM-DD HH:MM, YYYY-MM-DD (midnight) and HH:MM[:SS],
+// which is on the same day as the reference time (usually the current line's)
+// Times typed without an offset are taken to be in the reference time's offset
+pub fn parse_query(input: &str, reference: Option<Timestamp>) -> Option<Timestamp> {
+    let input: &str = input.trim();
+    let reference_offset: Option<i64> = reference.and_then(|r| r.offset);
+    let typed: Option<Timestamp> = parse_line(input)
+        .or_else(|| iso_8601(input, true))
+        .or_else(|| {
+            let mut s: Scanner = Scanner::new(input);
+            let year: i64 = s.digits(4)?;
+            s.byte(b'-')?;
+            let month: i64 = s.digits(2)?;
+            s.byte(b'-')?;
+            let day: i64 = s.digits(2)?;
+            s.peek().is_none().then_some(())?;
+            Some(with_offset(date(year, month, day)?, None))
+        })
+        .or_else(|| {
+            let mut s: Scanner = Scanner::new(input);
+            let time: i64 = s.time(true)?;
+            s.peek().is_none().then_some(())?;
+            let reference: Timestamp = reference?;
+            let local_day: i64 =
+                (reference.seconds + reference_offset.unwrap_or(0)).div_euclid(86400);
+            Some(with_offset(local_day * 86400 + time, None))
+        });
+    typed.map(|t| match t.offset {
+        Some(_) => t,
+        None => with_offset(t.seconds, reference_offset),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2020-10-11 22:14:15 UTC
+    const OCT_11_2020: i64 = 1602454455;
+
+    fn seconds(line: &str) -> Option<i64> {
+        parse_line(line).map(|t| t.seconds)
+    }
+
+    #[test]
+    fn converts_between_days_and_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(18321), (2020, 2, 29));
+        assert_eq!(civil_from_days(-719162), (1, 1, 1));
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(days_from_civil(2020, 2, 29), 18321);
+        for days in (-800_000..800_000).step_by(997) {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn reads_iso_8601_with_offsets_and_fractions() {
+        let utc: Timestamp = parse_line("2020-10-11T22:14:15.003Z GET /").unwrap();
+        assert_eq!((utc.seconds, utc.offset), (OCT_11_2020, Some(0)));
+        let colon: Timestamp = parse_line("2020-10-11T22:14:15+02:00 GET /").unwrap();
+        assert_eq!(
+            (colon.seconds, colon.offset),
+            (OCT_11_2020 - 7200, Some(7200))
+        );
+        let no_colon: Timestamp = parse_line("[2020-10-11 22:14:15 -0130] GET /").unwrap();
+        assert_eq!(
+            (no_colon.seconds, no_colon.offset),
+            (OCT_11_2020 + 5400, Some(-5400))
+        );
+        let comma: Timestamp = parse_line("2020-10-11 22:14:15,123 INFO start").unwrap();
+        assert_eq!((comma.seconds, comma.offset), (OCT_11_2020, None));
+        assert_eq!(seconds("2020-10-11 22:14 INFO no seconds"), None);
+        assert_eq!(seconds("2020-13-11 22:14:15 INFO no such month"), None);
+    }
+
+    #[test]
+    fn reads_syslog_with_a_padded_day_as_this_year() {
+        let padded: Timestamp = parse_line("Oct  1 02:03:04 host sshd[42]: hello").unwrap();
+        assert_eq!(
+            padded.display(),
+            format!("{}-10-01 02:03:04", current_year())
+        );
+        let two_digits: Timestamp = parse_line("Oct 11 22:14:15 host cron: hi").unwrap();
+        assert_eq!(
+            two_digits.display(),
+            format!("{}-10-11 22:14:15", current_year())
+        );
+    }
+
+    #[test]
+    fn reads_epoch_seconds_and_milliseconds() {
+        assert_eq!(seconds("1602454455 started"), Some(OCT_11_2020));
+        assert_eq!(seconds("1602454455123 started"), Some(OCT_11_2020));
+        assert_eq!(seconds("1602454455.5 started"), Some(OCT_11_2020));
+        assert_eq!(seconds("160245445 nine digits"), None);
+        assert_eq!(seconds("2602454455 not a 1"), None);
+        assert_eq!(seconds("16024544551 eleven digits"), None);
+    }
+
+    #[test]
+    fn reads_access_log_times_after_the_client_address() {
+        let line: &str = "127.0.0.1 - - [10/Oct/2020:13:55:36 -0700] \"GET / HTTP/1.1\" 200 612";
+        let time: Timestamp = parse_line(line).unwrap();
+        assert_eq!((time.seconds, time.offset), (1602363336, Some(-25200)));
+        assert_eq!(time.display(), "2020-10-10 13:55:36 -07:00");
+    }
+
+    #[test]
+    fn reads_a_typed_time_on_the_reference_day_in_its_offset() {
+        // Just after midnight on the 12th where the log was written, but still the 11th in UTC
+        let reference: Option<Timestamp> = parse_line("2020-10-12T00:30:00+02:00 INFO");
+        let time: Timestamp = parse_query("14:30", reference).unwrap();
+        assert_eq!(time.display(), "2020-10-12 14:30:00 +02:00");
+        let date: Timestamp = parse_query("2020-10-11", reference).unwrap();
+        assert_eq!(date.display(), "2020-10-11 00:00:00 +02:00");
+        let utc: Timestamp = parse_query("2020-10-11T22:14:15Z", reference).unwrap();
+        assert_eq!(utc.seconds, OCT_11_2020);
+        assert!(parse_query("14:30", None).is_none());
+        assert!(parse_query("25:00", reference).is_none());
+    }
+}
@@ -0,0 +1,2745 @@
+use std::{
+    cmp::{max, min},
+    env, fs, mem,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::{mpsc::RecvTimeoutError, Arc},
+    time::Duration,
+};
+
+use crate::{
+    backend::{self, Backend, Input, MouseEvent},
+    browser::DirListing,
+    buffer::Buffer,
+    clipboard::copy_osc52,
+    complete::{common_prefix, complete_path, display_name},
+    diff::{self, align, AlignedDiff, DiffView},
+    file::{expand_path, load_file, read_lines, read_path, LoadError, PathContents},
+    help::HELP_MESSAGE,
+    json::{self, JsonView},
+    loglevel::{self, Level, LogView},
+    pane::{draw_matches, draw_rows, Layout, Pane, Rect, CURRENT_MATCH_COLOR, MATCH_COLOR},
+    pins::{draw_pins, free_color, Pin, PIN_COLORS},
+    prompt::LineEditor,
+    search::{FileMatches, MatchList, SearchJob, Source},
+    shell::{editor_command, pipe_lines},
+    table::{detect_delimiter, Table},
+    timestamp::{self, Timestamp},
+};
+
+// Struct that holds properties of the current window for easy reference
+// Originally, I was using variables scoped to the main method to track these values,
+// but that lead to lots of borrowing issues + the inability to move logic out of the main method
+pub struct WindowState {
+    window: Box<dyn Backend>,
+    // Shared so a search running on a worker thread can read them without a copy
+    lines: Arc<Vec<String>>,
+    screen_height: i32,
+    screen_width: i32,
+    content_top: i32,
+    content_bottom: i32,
+    content_len: i32,
+    // Every match of the last search in line order, the index of the one last jumped to, and whether it searched upwards (?)
+    search_results: Vec<(i32, i32, i32)>,
+    search_index: Option<usize>,
+    search_reverse: bool,
+    // Set when the highlighting has been cleared with Esc u, until the next search or jump to a result
+    search_hidden: bool,
+    // Set while browsing a directory instead of reading a file, along with the index of the highlighted line
+    listing: Option<DirListing>,
+    selected: i32,
+    // Set while viewing the results of a search across every buffer, which are selected like a directory listing's entries
+    matches: Option<MatchList>,
+    // Name and location of what's being viewed, path is None for things that don't exist on disk (ex: command output)
+    name: String,
+    path: Option<PathBuf>,
+    // Line set with m, used as the start of a range for commands like |
+    mark: Option<i32>,
+    // Every other open buffer, in the order [ and ] cycle through them
+    buffers: Vec<Buffer>,
+    // Line clicked with the mouse, used as the current line instead of the top line while it's still on the screen
+    clicked_line: Option<i32>,
+    // Whether least captures mouse events, and how many lines one step of the wheel scrolls
+    mouse_enabled: bool,
+    wheel_lines: i32,
+    // Set while selecting lines in visual mode, as the (line the selection started on, line being moved with j/k)
+    visual: Option<(i32, i32)>,
+    // Where the focused pane's content is drawn, the whole screen (minus the bottom line) unless it's been split
+    pane_top: i32,
+    pane_left: i32,
+    pane_height: i32,
+    pane_width: i32,
+    // How the screen is split into panes, which one is focused, and every other pane
+    layout: Layout,
+    focus: usize,
+    panes: Vec<Pane>,
+    next_pane_id: usize,
+    // Color pair to draw each line in, empty when the lines aren't colored (ex: the sides of a diff)
+    line_colors: Vec<i16>,
+    // Set while viewing one side of a diff, panes showing the other side scroll along with this one
+    diff: Option<Rc<DiffView>>,
+    // Set while viewing a JSON document pretty printed, the path of the current line is shown in the message area
+    json: Option<JsonView>,
+    // Set while viewing a delimited file as a table, its header takes up the top row of the pane so it never scrolls away
+    table: Option<Table>,
+    // Set while viewing a log, its lines are colored by level and the ones below a level can be hidden
+    log: Option<LogView>,
+    // Patterns highlighted in their own colors in every pane, in the order they were pinned
+    pins: Vec<Pin>,
+}
+
+impl WindowState {
+    // Constructor for window state, takes in the screen to draw to and lines
+    pub fn new(window: Box<dyn Backend>, lines: Vec<String>) -> WindowState {
+        // The max x and y values represent the very edge of the window, so we can't actually access them
+        let (rows, cols) = window.size();
+        let screen_height: i32 = rows - 1;
+        let screen_width: i32 = cols - 1;
+        let content_len = lines.len() as i32;
+        let content_top = 0;
+        let content_bottom: i32 = min(screen_height - 1, content_len); // Make sure to reserve an additional line for program text
+        let search_results: Vec<(i32, i32, i32)> = Vec::new();
+        WindowState {
+            window,
+            lines: Arc::new(lines),
+            screen_height,
+            screen_width,
+            content_top,
+            content_bottom,
+            content_len,
+            search_results,
+            search_index: None,
+            search_reverse: false,
+            search_hidden: false,
+            listing: None,
+            selected: 0,
+            matches: None,
+            name: String::new(),
+            path: None,
+            mark: None,
+            buffers: Vec::new(),
+            clicked_line: None,
+            mouse_enabled: false,
+            wheel_lines: 3,
+            visual: None,
+            pane_top: 0,
+            pane_left: 0,
+            pane_height: screen_height,
+            pane_width: screen_width + 1,
+            layout: Layout::Pane(0),
+            focus: 0,
+            panes: Vec::new(),
+            next_pane_id: 1,
+            line_colors: Vec::new(),
+            diff: None,
+            json: None,
+            table: None,
+            log: None,
+            pins: Vec::new(),
+        }
+    }
+
+    // Set up the screen for a session: the colors and the mouse
+    pub fn start(window: Box<dyn Backend>, mouse_enabled: bool, wheel_lines: i32) -> WindowState {
+        let mut state: WindowState = WindowState::new(window, Vec::new());
+        state.wheel_lines = wheel_lines;
+        state = state.set_mouse(mouse_enabled);
+
+        // Setup colors
+        state
+            .window
+            .init_pair(1, backend::COLOR_WHITE, backend::COLOR_BLACK);
+        state
+            .window
+            .init_pair(MATCH_COLOR, backend::COLOR_BLACK, backend::COLOR_GREEN);
+        state.window.init_pair(
+            CURRENT_MATCH_COLOR,
+            backend::COLOR_BLACK,
+            backend::COLOR_YELLOW,
+        );
+        state.window.init_pair(
+            diff::DELETED_COLOR,
+            backend::COLOR_RED,
+            backend::COLOR_BLACK,
+        );
+        state.window.init_pair(
+            diff::INSERTED_COLOR,
+            backend::COLOR_GREEN,
+            backend::COLOR_BLACK,
+        );
+        state.window.init_pair(
+            diff::CHANGED_COLOR,
+            backend::COLOR_YELLOW,
+            backend::COLOR_BLACK,
+        );
+        state.window.init_pair(
+            loglevel::DEBUG_COLOR,
+            backend::COLOR_CYAN,
+            backend::COLOR_BLACK,
+        );
+        state.window.init_pair(
+            loglevel::WARN_COLOR,
+            backend::COLOR_YELLOW,
+            backend::COLOR_BLACK,
+        );
+        state.window.init_pair(
+            loglevel::ERROR_COLOR,
+            backend::COLOR_RED,
+            backend::COLOR_BLACK,
+        );
+        state.window.init_pair(
+            loglevel::FATAL_COLOR,
+            backend::COLOR_WHITE,
+            backend::COLOR_RED,
+        );
+        for (pair, background, _name) in PIN_COLORS {
+            state
+                .window
+                .init_pair(*pair, backend::COLOR_BLACK, *background);
+        }
+        state
+    }
+
+    // Show lines that didn't come from a file (ex: another program's output), detecting JSON, tables and logs like a file
+    pub fn open_lines(self, name: String, lines: Vec<String>) -> WindowState {
+        let mut new_state: WindowState = self.replace_lines(lines);
+        new_state.name = name;
+        new_state.path = None;
+        new_state.detect_json().detect_table().detect_log()
+    }
+
+    // Jump to a given line, as long as it's within the bounds of the current window
+    // Effectively replaces the logic I had separated out into separate code blocks for down one line, up one line, etc
+    pub fn jump_to_line(self, n: &i32) -> WindowState {
+        if *n >= 0 && *n <= self.content_len {
+            let new_content_top = *n;
+            let new_content_bottom = min(*n + self.pane_height, self.content_len);
+            self.draw_lines(new_content_top, new_content_bottom);
+            let mut new_state: WindowState = self;
+            new_state.content_top = new_content_top;
+            new_state.content_bottom = new_content_bottom;
+            new_state.scroll_linked_panes();
+            if let Some(json) = &new_state.json {
+                let path: &str = json.path(new_state.current_line()).unwrap_or("");
+                new_state.show_message(path);
+            } else if !new_state.selecting() {
+                if let Some((_line, time)) = new_state.line_timestamp(new_state.current_line()) {
+                    new_state.show_message(&time.display());
+                }
+            }
+            new_state
+        } else {
+            self
+        }
+    }
+
+    // Clear the focused pane and print the lines in [top, bottom) from its top left corner down
+    fn draw_lines(&self, top: i32, bottom: i32) {
+        let rect: Rect = self.pane_rect();
+        draw_rows(
+            self.window.as_ref(),
+            &self.lines[..bottom as usize],
+            &self.line_colors,
+            top,
+            &rect,
+        );
+        draw_pins(
+            self.window.as_ref(),
+            &self.pins,
+            &self.lines[..bottom as usize],
+            top,
+            &rect,
+        );
+        if !self.search_hidden {
+            draw_matches(
+                self.window.as_ref(),
+                &self.search_results,
+                self.search_index,
+                &self.lines[..bottom as usize],
+                top,
+                &rect,
+            );
+        }
+        // Mark the selected entry when browsing a directory or the results of a global search
+        if self.selecting() && self.selected >= top && self.selected < bottom {
+            self.highlight_row(self.selected - top, backend::A_REVERSE);
+        }
+        // Highlight the lines selected in visual mode
+        if let Some((start, end)) = self.visual_range() {
+            for line in max(start, top)..min(end, bottom) {
+                self.highlight_row(line - top, backend::A_REVERSE);
+            }
+        }
+        // Underline the line that was clicked on
+        if let Some(line) = self.clicked_line.filter(|l| *l >= top && *l < bottom) {
+            self.highlight_row(line - top, backend::A_UNDERLINE);
+        }
+        if let Some(table) = &self.table {
+            self.draw_table_header(table, &rect);
+        }
+        if !self.panes.is_empty() {
+            self.draw_pane_title(&rect, &self.name, true);
+        }
+        self.window.refresh();
+    }
+
+    // Draw a table's header in bold on the row above the given content area
+    fn draw_table_header(&self, table: &Table, content: &Rect) {
+        let header_rect: Rect = Rect {
+            top: content.top - 1,
+            height: 1,
+            ..*content
+        };
+        draw_rows(
+            self.window.as_ref(),
+            &[table.header()],
+            &[],
+            0,
+            &header_rect,
+        );
+        self.window.mvchgat(
+            header_rect.top,
+            header_rect.left,
+            header_rect.width,
+            backend::A_BOLD | backend::A_UNDERLINE,
+            1,
+        );
+    }
+
+    // Rows at the top of the focused pane that its lines don't scroll through, aka a table's header
+    fn frozen_rows(&self) -> i32 {
+        if self.table.is_some() {
+            1
+        } else {
+            0
+        }
+    }
+
+    // Swap the table being viewed for another (or None), giving the header its row at the top of the pane
+    // Returns the one that was there before
+    fn replace_table(&mut self, table: Option<Table>) -> Option<Table> {
+        let old_frozen_rows: i32 = self.frozen_rows();
+        let old_table: Option<Table> = mem::replace(&mut self.table, table);
+        let change: i32 = self.frozen_rows() - old_frozen_rows;
+        self.pane_top += change;
+        self.pane_height -= change;
+        old_table
+    }
+
+    // Apply an attribute to a whole row of the focused pane, without spilling over into the panes next to it
+    fn highlight_row(&self, row: i32, attributes: backend::Attributes) {
+        self.window.mvchgat(
+            self.pane_top + row,
+            self.pane_left,
+            self.pane_width,
+            attributes,
+            1,
+        );
+    }
+
+    // The focused pane's content area
+    fn pane_rect(&self) -> Rect {
+        Rect {
+            top: self.pane_top,
+            left: self.pane_left,
+            height: self.pane_height,
+            width: self.pane_width,
+        }
+    }
+
+    // Draw the title bar under a pane's content area, with the name of its buffer
+    // The focused pane's title is drawn in the highlight color so it's easy to tell which pane keys go to
+    fn draw_pane_title(&self, content: &Rect, name: &str, focused: bool) {
+        let title: String = format!(" {}", name)
+            .chars()
+            .take(max(0, content.width) as usize)
+            .collect();
+        if focused {
+            self.window.attrset(backend::A_NORMAL, 2);
+        } else {
+            self.window.attrset(backend::A_REVERSE, 0);
+        }
+        self.window.mvaddstr(
+            content.top + content.height,
+            content.left,
+            &format!("{:<width$}", title, width = max(0, content.width) as usize),
+        );
+        self.window.attrset(backend::A_NORMAL, 1);
+    }
+
+    // Highlight the search results of a pane that isn't focused, unless they've been cleared
+    fn draw_pane_matches(&self, pane: &Pane, content: &Rect) {
+        if !pane.buffer.search_hidden {
+            draw_matches(
+                self.window.as_ref(),
+                &pane.buffer.search_results,
+                pane.buffer.search_index,
+                &pane.buffer.lines,
+                pane.buffer.content_top,
+                content,
+            );
+        }
+    }
+
+    // Redraw everything: the panes that aren't focused, the separators between side by side panes, and the focused pane
+    // Needed after anything that draws over the whole screen, like the help menu or a completion popup
+    fn draw_screen(&self) {
+        self.window.erase();
+        for pane in &self.panes {
+            let mut content: Rect = pane.rect;
+            if let Some(table) = &pane.buffer.table {
+                content.top += 1;
+                content.height -= 1;
+                self.draw_table_header(table, &content);
+            }
+            draw_rows(
+                self.window.as_ref(),
+                &pane.buffer.lines,
+                &pane.buffer.line_colors,
+                pane.buffer.content_top,
+                &content,
+            );
+            draw_pins(
+                self.window.as_ref(),
+                &self.pins,
+                &pane.buffer.lines,
+                pane.buffer.content_top,
+                &content,
+            );
+            self.draw_pane_matches(pane, &content);
+            self.draw_pane_title(&pane.rect, &pane.buffer.name, false);
+        }
+        let mut separators: Vec<(i32, i32, i32)> = Vec::new();
+        self.layout.separators(self.screen_area(), &mut separators);
+        for (col, top, height) in separators {
+            for row in top..top + height {
+                self.window.mvaddch(row, col, '|');
+            }
+        }
+        self.draw_lines(self.content_top, self.content_bottom);
+    }
+
+    // The part of the screen that's split between panes, aka everything but the bottom line
+    fn screen_area(&self) -> Rect {
+        Rect {
+            top: 0,
+            left: 0,
+            height: self.screen_height,
+            width: self.screen_width + 1,
+        }
+    }
+
+    // Work out where every pane goes from the layout, then redraw the whole screen
+    // When there's more than one pane, each one loses its bottom row to a title bar
+    fn layout_panes(self) -> WindowState {
+        let mut rects: Vec<(usize, Rect)> = Vec::new();
+        self.layout.rects(self.screen_area(), &mut rects);
+        let title_rows: i32 = if rects.len() > 1 { 1 } else { 0 };
+        let mut new_state: WindowState = self;
+        for (id, rect) in rects {
+            let content: Rect = Rect {
+                height: max(1, rect.height - title_rows),
+                ..rect
+            };
+            if id == new_state.focus {
+                let frozen_rows: i32 = new_state.frozen_rows();
+                new_state.pane_top = content.top + frozen_rows;
+                new_state.pane_left = content.left;
+                new_state.pane_height = max(1, content.height - frozen_rows);
+                new_state.pane_width = content.width;
+            } else if let Some(pane) = new_state.panes.iter_mut().find(|p| p.id == id) {
+                pane.rect = content;
+            }
+        }
+        // Recalculate content_bottom for the focused pane's new size
+        let top: i32 = new_state.content_top;
+        new_state.content_bottom = min(top + new_state.pane_height, new_state.content_len);
+        new_state.draw_screen();
+        new_state
+    }
+
+    // Id of the pane that the given screen position falls in, if any
+    fn pane_at(&self, y: i32, x: i32) -> Option<usize> {
+        let mut rects: Vec<(usize, Rect)> = Vec::new();
+        self.layout.rects(self.screen_area(), &mut rects);
+        rects
+            .into_iter()
+            .find(|(_id, r)| {
+                y >= r.top && y < r.top + r.height && x >= r.left && x < r.left + r.width
+            })
+            .map(|(id, _r)| id)
+    }
+
+    // Copy of the buffer being viewed, for showing it in a second pane
+    fn clone_buffer(&self) -> Buffer {
+        Buffer {
+            name: self.name.clone(),
+            path: self.path.clone(),
+            lines: self.lines.clone(),
+            content_top: self.content_top,
+            search_results: self.search_results.clone(),
+            search_index: self.search_index,
+            search_reverse: self.search_reverse,
+            search_hidden: self.search_hidden,
+            listing: self.listing.clone(),
+            selected: self.selected,
+            matches: self.matches.clone(),
+            mark: self.mark,
+            line_colors: self.line_colors.clone(),
+            diff: self.diff.clone(),
+            json: self.json.clone(),
+            table: self.table.clone(),
+            log: self.log.clone(),
+        }
+    }
+
+    // Split the focused pane in two, side by side if vertical is true and stacked otherwise
+    // The new pane shows the same buffer at the same position, and takes the focus
+    pub fn split_pane(self, vertical: bool) -> WindowState {
+        let mut new_state: WindowState = self;
+        let new_id: usize = new_state.next_pane_id;
+        new_state.next_pane_id += 1;
+        new_state.layout.split(new_state.focus, new_id, vertical);
+        let copy: Buffer = new_state.clone_buffer();
+        let current: Buffer = new_state.take_buffer();
+        new_state.panes.push(Pane {
+            id: new_state.focus,
+            buffer: current,
+            rect: new_state.pane_rect(),
+        });
+        new_state.focus = new_id;
+        new_state.load_buffer(copy);
+        new_state.layout_panes()
+    }
+
+    // Move the focus to the pane with the given id
+    fn focus_pane(self, id: usize) -> WindowState {
+        let index: usize = match self.panes.iter().position(|p| p.id == id) {
+            Some(index) => index,
+            None => return self,
+        };
+        let mut new_state: WindowState = self;
+        let target: Pane = new_state.panes.remove(index);
+        let current: Buffer = new_state.take_buffer();
+        new_state.panes.push(Pane {
+            id: new_state.focus,
+            buffer: current,
+            rect: new_state.pane_rect(),
+        });
+        new_state.focus = target.id;
+        new_state.load_buffer(target.buffer);
+        new_state.layout_panes()
+    }
+
+    // Move the focus to the next (or previous) pane, going from top left to bottom right
+    pub fn cycle_panes(self, forward: bool) -> WindowState {
+        let ids: Vec<usize> = self.layout.pane_ids();
+        let position: usize = ids.iter().position(|id| *id == self.focus).unwrap_or(0);
+        let next: usize = if forward {
+            (position + 1) % ids.len()
+        } else {
+            (position + ids.len() - 1) % ids.len()
+        };
+        self.focus_pane(ids[next])
+    }
+
+    // Close the focused pane, giving its space back to the pane it was split from
+    // The buffer it was showing stays open in the background, so [ and ] can still get back to it
+    pub fn close_pane(self) -> WindowState {
+        let mut new_state: WindowState = self;
+        if !new_state.layout.remove(new_state.focus) {
+            new_state.show_message("Can't close the only pane");
+            return new_state;
+        }
+        let closed: Buffer = new_state.take_buffer();
+        new_state.buffers.push(closed);
+        let next_id: usize = new_state.layout.pane_ids()[0];
+        let index: usize = new_state
+            .panes
+            .iter()
+            .position(|p| p.id == next_id)
+            .expect("Layout refers to a pane that doesn't exist");
+        let next: Pane = new_state.panes.remove(index);
+        new_state.focus = next.id;
+        new_state.load_buffer(next.buffer);
+        new_state.layout_panes()
+    }
+
+    // Make the focused pane bigger (or smaller, with a negative amount) by a percentage of the space it shares
+    // If vertical is true, it gets wider, otherwise it gets taller
+    pub fn resize_pane(self, vertical: bool, amount: i32) -> WindowState {
+        let mut new_state: WindowState = self;
+        if new_state.layout.resize(new_state.focus, vertical, amount) {
+            new_state.layout_panes()
+        } else {
+            new_state.show_message(if vertical {
+                "No side by side panes to resize"
+            } else {
+                "No stacked panes to resize"
+            });
+            new_state
+        }
+    }
+
+    // Scroll every other pane showing a side of the same diff to the focused pane's position, so both sides stay lined up
+    fn scroll_linked_panes(&mut self) {
+        let diff: &Rc<DiffView> = match &self.diff {
+            Some(diff) => diff,
+            None => return,
+        };
+        for pane in self.panes.iter_mut() {
+            if pane
+                .buffer
+                .diff
+                .as_ref()
+                .is_some_and(|d| Rc::ptr_eq(d, diff))
+            {
+                pane.buffer.content_top = self.content_top;
+                draw_rows(
+                    self.window.as_ref(),
+                    &pane.buffer.lines,
+                    &pane.buffer.line_colors,
+                    pane.buffer.content_top,
+                    &pane.rect,
+                );
+                draw_pins(
+                    self.window.as_ref(),
+                    &self.pins,
+                    &pane.buffer.lines,
+                    pane.buffer.content_top,
+                    &pane.rect,
+                );
+                if !pane.buffer.search_hidden {
+                    draw_matches(
+                        self.window.as_ref(),
+                        &pane.buffer.search_results,
+                        pane.buffer.search_index,
+                        &pane.buffer.lines,
+                        pane.buffer.content_top,
+                        &pane.rect,
+                    );
+                }
+            }
+        }
+        self.window.refresh();
+    }
+
+    // Show the differences between two sets of lines side by side, in a vertical split of the focused pane
+    // The buffer being viewed stays open in the background (unless it's the empty one least starts with when given --diff)
+    pub fn open_diff(
+        self,
+        left_name: String,
+        left_lines: &[String],
+        right_name: String,
+        right_lines: &[String],
+    ) -> WindowState {
+        let aligned: AlignedDiff = align(left_lines, right_lines);
+        let view: Rc<DiffView> = Rc::new(aligned.view);
+        let mut left: Buffer = Buffer::new(left_name, None, aligned.left);
+        left.line_colors = aligned.left_colors;
+        left.diff = Some(Rc::clone(&view));
+        let mut right: Buffer = Buffer::new(right_name, None, aligned.right);
+        right.line_colors = aligned.right_colors;
+        right.diff = Some(Rc::clone(&view));
+        let mut new_state: WindowState = self;
+        let current: Buffer = new_state.take_buffer();
+        if !current.name.is_empty() {
+            new_state.buffers.push(current);
+        }
+        let left_id: usize = new_state.focus;
+        new_state.load_buffer(left);
+        new_state = new_state.split_pane(true);
+        new_state.load_buffer(right);
+        new_state = new_state.focus_pane(left_id);
+        new_state.show_message(&match view.hunks.len() {
+            0 => String::from("No differences"),
+            1 => String::from("1 hunk, { and } to move between hunks"),
+            n => format!("{} hunks, {{ and }} to move between hunks", n),
+        });
+        new_state
+    }
+
+    // Prompt for a path (with Tab completion) and diff the current buffer against it, see open_diff()
+    pub fn diff_file(self) -> WindowState {
+        self.show_message("Diff against:");
+        match self.read_input(true) {
+            Some(filename) if !filename.is_empty() => match load_file(&filename) {
+                Ok(other_lines) => {
+                    let name: String = self.name.clone();
+                    let lines: Arc<Vec<String>> = Arc::clone(&self.lines);
+                    self.open_diff(name, &lines, filename, &other_lines)
+                }
+                Err(e) => {
+                    self.show_message(&e.to_string());
+                    self
+                }
+            },
+            _ => {
+                self.show_message("");
+                self
+            }
+        }
+    }
+
+    // Scroll to the next (or previous) hunk of the diff being viewed, leaving a few lines of context above it
+    pub fn jump_to_hunk(self, forward: bool) -> WindowState {
+        let context: i32 = 3;
+        let top: i32 = self.content_top;
+        let target: Option<(usize, usize, i32)> = match &self.diff {
+            Some(diff) => {
+                let position: Option<usize> = if forward {
+                    diff.hunks.iter().position(|h| max(0, h - context) > top)
+                } else {
+                    diff.hunks.iter().rposition(|h| max(0, h - context) < top)
+                };
+                position.map(|i| (i, diff.hunks.len(), diff.hunks[i]))
+            }
+            None => {
+                self.show_message("Not viewing a diff, press D to diff against a file");
+                return self;
+            }
+        };
+        match target {
+            Some((index, count, hunk)) => {
+                let new_top: i32 = max(0, hunk - context);
+                let new_state: WindowState = self.jump_to_line(&new_top);
+                new_state.show_message(&format!("Hunk {} of {}", index + 1, count));
+                new_state
+            }
+            None => {
+                self.show_message(if forward {
+                    "No more hunks below"
+                } else {
+                    "No more hunks above"
+                });
+                self
+            }
+        }
+    }
+
+    // Swap in a new set of lines (ex: a freshly loaded file) and draw them from the top
+    fn replace_lines(self, lines: Vec<String>) -> WindowState {
+        let mut new_state: WindowState = self;
+        new_state.content_len = lines.len() as i32;
+        new_state.lines = Arc::new(lines);
+        new_state.search_results = Vec::new();
+        new_state.search_index = None;
+        new_state.listing = None;
+        new_state.matches = None;
+        new_state.mark = None;
+        new_state.line_colors = Vec::new();
+        new_state.diff = None;
+        new_state.json = None;
+        new_state.replace_table(None);
+        new_state.log = None;
+        new_state.clicked_line = None;
+        new_state.visual = None;
+        new_state.jump_to_line(&0)
+    }
+
+    // The line that commands like m and | treat as "here": the clicked line if it's on the screen, otherwise the top line
+    fn current_line(&self) -> i32 {
+        match self.clicked_line {
+            Some(line) if line >= self.content_top && line < self.content_bottom => line,
+            _ => self.content_top,
+        }
+    }
+
+    // Whether lines are picked from a list with a selection (a directory listing or global search results)
+    fn selecting(&self) -> bool {
+        self.listing.is_some() || self.matches.is_some()
+    }
+
+    // Turn capturing of mouse events on or off
+    // While it's off, the terminal handles the mouse itself, so its native text selection works
+    pub fn set_mouse(self, enabled: bool) -> WindowState {
+        self.window.set_mouse(enabled);
+        let mut new_state: WindowState = self;
+        new_state.mouse_enabled = enabled;
+        new_state
+    }
+
+    // Handle a mouse event: the wheel scrolls by wheel_lines, and a click makes the clicked line the current line
+    // (or selects the clicked entry when browsing a directory)
+    pub fn handle_mouse(self) -> WindowState {
+        match self.window.getmouse() {
+            Some(MouseEvent::WheelUp) => {
+                let new_pos: i32 = max(0, self.content_top - self.wheel_lines);
+                self.jump_to_line(&new_pos)
+            }
+            Some(MouseEvent::WheelDown) => {
+                let new_pos: i32 = min(self.content_top + self.wheel_lines, self.content_len);
+                self.jump_to_line(&new_pos)
+            }
+            Some(MouseEvent::Click { y, x }) if y < self.screen_height => {
+                // Clicking on another pane just focuses it
+                match self.pane_at(y, x) {
+                    Some(id) if id != self.focus => return self.focus_pane(id),
+                    _ => (),
+                }
+                let line: i32 = self.content_top + y - self.pane_top;
+                if line >= self.content_bottom
+                    || y < self.pane_top
+                    || y >= self.pane_top + self.pane_height
+                {
+                    self
+                } else if self.selecting() {
+                    self.select_entry(line)
+                } else {
+                    let mut new_state: WindowState = self;
+                    new_state.clicked_line = Some(line);
+                    let top: i32 = new_state.content_top;
+                    new_state = new_state.jump_to_line(&top);
+                    new_state.show_message(&format!("Line {}", line + 1));
+                    new_state
+                }
+            }
+            _ => self,
+        }
+    }
+
+    // Move the buffer being viewed out of the window state, so it can be stashed away in buffers
+    fn take_buffer(&mut self) -> Buffer {
+        Buffer {
+            name: mem::take(&mut self.name),
+            path: self.path.take(),
+            lines: mem::take(&mut self.lines),
+            content_top: self.content_top,
+            search_results: mem::take(&mut self.search_results),
+            search_index: self.search_index.take(),
+            search_reverse: self.search_reverse,
+            search_hidden: self.search_hidden,
+            listing: self.listing.take(),
+            selected: self.selected,
+            matches: self.matches.take(),
+            mark: self.mark.take(),
+            line_colors: mem::take(&mut self.line_colors),
+            diff: self.diff.take(),
+            json: self.json.take(),
+            table: self.replace_table(None),
+            log: self.log.take(),
+        }
+    }
+
+    // Move a buffer into the window state, without drawing it
+    fn load_buffer(&mut self, buffer: Buffer) {
+        self.content_len = buffer.lines.len() as i32;
+        self.content_top = buffer.content_top;
+        self.name = buffer.name;
+        self.path = buffer.path;
+        self.lines = buffer.lines;
+        self.search_results = buffer.search_results;
+        self.search_index = buffer.search_index;
+        self.search_reverse = buffer.search_reverse;
+        self.search_hidden = buffer.search_hidden;
+        self.listing = buffer.listing;
+        self.selected = buffer.selected;
+        self.matches = buffer.matches;
+        self.mark = buffer.mark;
+        self.line_colors = buffer.line_colors;
+        self.diff = buffer.diff;
+        self.json = buffer.json;
+        self.replace_table(buffer.table);
+        self.log = buffer.log;
+        self.clicked_line = None;
+        self.visual = None;
+    }
+
+    // View the given buffer, from wherever it was last scrolled to
+    fn show_buffer(self, buffer: Buffer) -> WindowState {
+        let mut new_state: WindowState = self;
+        new_state.load_buffer(buffer);
+        let top: i32 = new_state.content_top;
+        new_state.jump_to_line(&top)
+    }
+
+    // Switch to a new buffer, keeping the current one open in the background
+    pub fn open_buffer(self, buffer: Buffer) -> WindowState {
+        let mut new_state: WindowState = self;
+        let current: Buffer = new_state.take_buffer();
+        new_state.buffers.push(current);
+        new_state.show_buffer(buffer)
+    }
+
+    // Switch to the next (or previous) open buffer, and say which one it is in the message area
+    pub fn cycle_buffers(self, forward: bool) -> WindowState {
+        if self.buffers.is_empty() {
+            self.show_message("No other buffers open");
+            return self;
+        }
+        let mut new_state: WindowState = self;
+        let current: Buffer = new_state.take_buffer();
+        let next: Buffer = if forward {
+            new_state.buffers.push(current);
+            new_state.buffers.remove(0)
+        } else {
+            new_state.buffers.insert(0, current);
+            new_state.buffers.pop().unwrap()
+        };
+        new_state = new_state.show_buffer(next);
+        new_state.show_message(&format!(
+            "{} ({} other buffers open)",
+            new_state.name,
+            new_state.buffers.len()
+        ));
+        new_state
+    }
+
+    // Write a message in the bottom left of the screen, it stays until the next redraw
+    pub fn show_message(&self, message: &str) {
+        self.window.mv(self.screen_height, 0);
+        self.window.clrtoeol();
+        self.window.addnstr(message, self.screen_width as usize);
+        self.window.refresh();
+    }
+
+    // Open whatever is at the given path: directories are browsed, anything else is read as lines
+    // If it can't be read, the error is shown in the message area and the current buffer stays on screen
+    pub fn open_path(self, filename: &str) -> WindowState {
+        match read_path(filename) {
+            Ok(contents) => self.show_path(filename, contents),
+            Err(e) => {
+                self.show_message(&e.to_string());
+                self
+            }
+        }
+    }
+
+    // Show what was read from a path (see read_path()) in place of the current buffer
+    fn show_path(self, filename: &str, contents: PathContents) -> WindowState {
+        match contents {
+            PathContents::Listing(listing) => self.show_listing(listing),
+            PathContents::Lines(path, lines) => {
+                let mut new_state: WindowState = self.replace_lines(lines);
+                new_state.name = filename.to_string();
+                new_state.path = Some(path);
+                new_state.detect_json().detect_table().detect_log()
+            }
+        }
+    }
+
+    // Switch to JSON mode if the buffer looks like JSON: its name ends in .json, or it starts with { or [ and parses
+    // Only a file named .json that doesn't parse says so, anything else that doesn't parse is just shown as it is
+    fn detect_json(self) -> WindowState {
+        let named_json: bool = self.name.to_lowercase().ends_with(".json");
+        let starts_like_json: bool = self
+            .lines
+            .iter()
+            .flat_map(|l| l.chars())
+            .find(|c| !c.is_whitespace())
+            .is_some_and(|c| c == '{' || c == '[');
+        if !named_json && !starts_like_json {
+            return self;
+        }
+        match json::parse(&self.lines.join("\n")) {
+            Ok(document) => {
+                let view: JsonView = JsonView::new(self.lines.to_vec(), document);
+                self.show_json(view)
+            }
+            Err(e) => {
+                if named_json {
+                    self.show_message(&format!("Not valid JSON, showing it as is: {}", e));
+                }
+                self
+            }
+        }
+    }
+
+    // Replace the lines with the pretty printed JSON document, see json::JsonView
+    fn show_json(self, view: JsonView) -> WindowState {
+        let mut view: JsonView = view;
+        let lines: Vec<String> = view.render();
+        let mut new_state: WindowState = self.replace_lines(lines);
+        new_state.json = Some(view);
+        new_state.jump_to_line(&0)
+    }
+
+    // Switch between viewing the buffer as pretty printed JSON and viewing its lines as they are
+    pub fn toggle_json(self) -> WindowState {
+        let mut new_state: WindowState = self;
+        match new_state.json.take() {
+            Some(view) => {
+                new_state = new_state.replace_lines(view.raw);
+                new_state.show_message("JSON mode off");
+                new_state
+            }
+            None => match json::parse(&new_state.lines.join("\n")) {
+                Ok(document) => {
+                    let view: JsonView = JsonView::new(new_state.lines.to_vec(), document);
+                    new_state.show_json(view)
+                }
+                Err(e) => {
+                    new_state.show_message(&format!("Not valid JSON: {}", e));
+                    new_state
+                }
+            },
+        }
+    }
+
+    // Fold or unfold the JSON array or object at the current line (or every one of them, if all is true)
+    // The fold's first line stays where it was on the screen, unless it was above the top of it
+    pub fn toggle_fold(self, all: bool) -> WindowState {
+        let mut new_state: WindowState = self;
+        let mut view: JsonView = match new_state.json.take() {
+            Some(view) => view,
+            None => {
+                new_state.show_message("Not viewing JSON, press J to switch to JSON mode");
+                return new_state;
+            }
+        };
+        let here: i32 = new_state.current_line();
+        let toggled: Option<usize> = if all {
+            view.toggle_fold_all();
+            None
+        } else {
+            match view.toggle_fold(here) {
+                Some(id) => Some(id),
+                None => {
+                    new_state.json = Some(view);
+                    new_state.show_message("Nothing to fold here");
+                    return new_state;
+                }
+            }
+        };
+        new_state.lines = Arc::new(view.render());
+        new_state.content_len = new_state.lines.len() as i32;
+        // Line numbers after the fold have moved, so search results and marks would point at the wrong lines
+        new_state.search_results = Vec::new();
+        new_state.search_index = None;
+        new_state.mark = None;
+        new_state.clicked_line = None;
+        let new_top: i32 = match toggled.and_then(|id| view.start_line(id)) {
+            Some(start) if start < new_state.content_top => start,
+            _ => min(new_state.content_top, max(0, new_state.content_len - 1)),
+        };
+        new_state.json = Some(view);
+        new_state.jump_to_line(&new_top)
+    }
+
+    // Switch to table mode for files named .csv or .tsv, as long as a delimiter can be found
+    fn detect_table(self) -> WindowState {
+        let name: String = self.name.to_lowercase();
+        if self.json.is_some() || !(name.ends_with(".csv") || name.ends_with(".tsv")) {
+            return self;
+        }
+        match detect_delimiter(&self.lines).and_then(|d| Table::new(self.lines.to_vec(), d)) {
+            Some(table) => self.show_table(table),
+            None => self,
+        }
+    }
+
+    // Replace the lines with the rows of the table, with its header frozen at the top of the pane
+    fn show_table(self, table: Table) -> WindowState {
+        let lines: Vec<String> = table.render();
+        let mut new_state: WindowState = self.replace_lines(lines);
+        new_state.replace_table(Some(table));
+        new_state.content_bottom = min(new_state.pane_height, new_state.content_len);
+        new_state.draw_screen();
+        new_state
+    }
+
+    // Switch between viewing the buffer as a table and viewing its lines as they are
+    pub fn toggle_table(self) -> WindowState {
+        let mut new_state: WindowState = self;
+        match new_state.replace_table(None) {
+            Some(table) => {
+                new_state = new_state.replace_lines(table.raw);
+                new_state.draw_screen();
+                new_state.show_message("Table mode off");
+                new_state
+            }
+            None => match detect_delimiter(&new_state.lines)
+                .and_then(|d| Table::new(new_state.lines.to_vec(), d))
+            {
+                Some(table) => {
+                    let delimiter: String = match table.delimiter {
+                        '\t' => String::from("tabs"),
+                        d => format!("'{}'", d),
+                    };
+                    new_state = new_state.show_table(table);
+                    new_state.show_message(&format!("Table mode on, split on {}", delimiter));
+                    new_state
+                }
+                None => {
+                    new_state.show_message(
+                        "Can't find a delimiter that splits every line into the same columns",
+                    );
+                    new_state
+                }
+            },
+        }
+    }
+
+    // Scroll a table left or right by a number of columns
+    pub fn scroll_columns(self, amount: i32) -> WindowState {
+        let first_column: usize = match &self.table {
+            Some(table) => {
+                let last_column: i32 = table.column_count() as i32 - 1;
+                max(0, min(table.first_column as i32 + amount, last_column)) as usize
+            }
+            None => return self,
+        };
+        self.scroll_to_column(first_column)
+    }
+
+    // Make the given column the first one on the screen
+    fn scroll_to_column(self, first_column: usize) -> WindowState {
+        let mut new_state: WindowState = self;
+        let mut table: Table = match new_state.table.take() {
+            Some(table) => table,
+            None => return new_state,
+        };
+        table.scroll_to(first_column, &mut new_state.search_results);
+        new_state.search_index = None;
+        new_state.lines = Arc::new(table.render());
+        let message: String = format!(
+            "Column {} of {}: {}",
+            first_column + 1,
+            table.column_count(),
+            table.column_name(first_column)
+        );
+        new_state.table = Some(table);
+        let top: i32 = new_state.content_top;
+        new_state = new_state.jump_to_line(&top);
+        new_state.show_message(&message);
+        new_state
+    }
+
+    // Ask for a column (by name or number) and a term, and search for it in just that column of the table
+    pub fn search_column(self) -> WindowState {
+        if self.table.is_none() {
+            self.show_message("Not viewing a table, press T to switch to table mode");
+            return self;
+        }
+        self.show_message("Search column:");
+        let column: Option<usize> = match self.read_input(false) {
+            Some(name) => self.table.as_ref().and_then(|t| t.find_column(&name)),
+            None => {
+                self.show_message("");
+                return self;
+            }
+        };
+        let column: usize = match column {
+            Some(column) => column,
+            None => {
+                self.show_message("No such column");
+                return self;
+            }
+        };
+        let name: String = self
+            .table
+            .as_ref()
+            .map(|t| t.column_name(column))
+            .unwrap_or_default();
+        self.show_message(&format!("Search {}:", name));
+        let term: String = match self.read_input(false) {
+            Some(term) => term,
+            None => {
+                self.show_message("");
+                return self;
+            }
+        };
+        // Bring the column on screen first, since results are found in the lines as they're drawn
+        let mut new_state: WindowState = self;
+        let visible: bool = new_state
+            .table
+            .as_ref()
+            .and_then(|t| t.screen_offset(column))
+            .is_some_and(|offset| (offset as i32) < new_state.pane_width);
+        if !visible {
+            new_state = new_state.scroll_to_column(column);
+        }
+        let results: Vec<(i32, i32, i32)> = new_state
+            .table
+            .as_mut()
+            .map(|t| t.search_column(column, &term))
+            .unwrap_or_default();
+        if results.is_empty() {
+            new_state.show_message(&format!("\"{}\" not found in {}", term, name));
+            new_state
+        } else {
+            new_state.start_search(results, false)
+        }
+    }
+
+    // The timestamp of a log line, from the nearest line at or above it that has one (ex: for the lines of a stack trace)
+    // Returns the line it was found on along with the timestamp
+    fn line_timestamp(&self, line: i32) -> Option<(i32, Timestamp)> {
+        // Don't look back forever through a file that has no timestamps at all
+        let oldest: i32 = max(0, line - 200);
+        (oldest..=line)
+            .rev()
+            .filter_map(|l| self.lines.get(l as usize).map(|text| (l, text)))
+            .find_map(|(l, text)| timestamp::parse_line(text).map(|t| (l, t)))
+    }
+
+    // Ask for a time and jump to the first line at or after it, assuming the lines are in time order
+    // Binary searches the lines, so it's quick even on huge logs
+    pub fn jump_to_time(self) -> WindowState {
+        let reference: Option<Timestamp> =
+            self.line_timestamp(self.current_line()).map(|(_l, t)| t);
+        if reference.is_none()
+            && !self
+                .lines
+                .iter()
+                .any(|l| timestamp::parse_line(l).is_some())
+        {
+            self.show_message("No timestamps found");
+            return self;
+        }
+        self.show_message("Jump to time:");
+        let input: String = match self.read_input(false) {
+            Some(input) if !input.trim().is_empty() => input,
+            _ => {
+                self.show_message("");
+                return self;
+            }
+        };
+        let target: Timestamp = match timestamp::parse_query(&input, reference) {
+            Some(target) => target,
+            None => {
+                self.show_message(&format!("Can't read \"{}\" as a time", input));
+                return self;
+            }
+        };
+        // Find the first line whose timestamp is at or after the target, lines before the first timestamp count as earlier
+        let (mut low, mut high): (i32, i32) = (0, self.content_len);
+        while low < high {
+            let mid: i32 = low + (high - low) / 2;
+            match self.line_timestamp(mid) {
+                Some((_line, time)) if time.seconds >= target.seconds => high = mid,
+                _ => low = mid + 1,
+            }
+        }
+        if low >= self.content_len {
+            self.show_message(&format!("Nothing at or after {}", target.display()));
+            return self;
+        }
+        self.jump_to_line(&low)
+    }
+
+    // Color the lines by log level if they look like a log, see loglevel::is_log()
+    fn detect_log(self) -> WindowState {
+        if self.json.is_some() || self.table.is_some() || !loglevel::is_log(&self.lines) {
+            return self;
+        }
+        let mut new_state: WindowState = self;
+        let view: LogView = LogView::new(new_state.lines.to_vec());
+        new_state.line_colors = view.colors();
+        new_state.log = Some(view);
+        let top: i32 = new_state.content_top;
+        new_state.jump_to_line(&top)
+    }
+
+    // Ask for a level and hide every line below it, or show them all again
+    // The top line stays on the screen if it's still shown, otherwise the next line that is takes its place
+    pub fn filter_levels(self) -> WindowState {
+        if self.json.is_some() || self.table.is_some() {
+            self.show_message("Can't filter by level in JSON or table mode");
+            return self;
+        }
+        self.show_message(
+            "Show [t]race, [d]ebug, [i]nfo, [w]arn, [e]rror, [f]atal and above, or [a]ll?",
+        );
+        let min_level: Option<Level> = match self.window.getch() {
+            Some(Input::Character('t')) => Some(Level::Trace),
+            Some(Input::Character('d')) => Some(Level::Debug),
+            Some(Input::Character('i')) => Some(Level::Info),
+            Some(Input::Character('w')) => Some(Level::Warn),
+            Some(Input::Character('e')) => Some(Level::Error),
+            Some(Input::Character('f')) => Some(Level::Fatal),
+            Some(Input::Character('a')) => None,
+            _ => {
+                self.show_message("");
+                return self;
+            }
+        };
+        let new_state: WindowState = self.set_min_level(min_level);
+        let shown: i32 = new_state.content_len;
+        let total: usize = new_state.log.as_ref().map_or(0, |view| view.raw.len());
+        new_state.show_message(&match min_level {
+            Some(level) => format!(
+                "Showing {} and above ({} of {} lines)",
+                level.name(),
+                shown,
+                total
+            ),
+            None => format!("Showing all {} lines", total),
+        });
+        new_state
+    }
+
+    // Hide every line below a level, or show them all again with None, see LogView::filter()
+    // The top line stays on the screen if it's still shown, otherwise the next line that is takes its place
+    fn set_min_level(self, min_level: Option<Level>) -> WindowState {
+        let mut new_state: WindowState = self;
+        let mut view: LogView = match new_state.log.take() {
+            Some(view) => view,
+            None => LogView::new(new_state.lines.to_vec()),
+        };
+        let top: usize = view.original_line(new_state.content_top);
+        let (lines, colors) = view.filter(min_level);
+        let new_top: i32 = min(view.shown_line(top), max(0, lines.len() as i32 - 1));
+        new_state = new_state.replace_lines(lines);
+        new_state.line_colors = colors;
+        new_state.log = Some(view);
+        new_state.jump_to_line(&new_top)
+    }
+
+    // Jump to the next (or previous) WARN, ERROR or FATAL line
+    // The problems are stepped through the same way as search results, but kept apart from them, so n and N still go
+    // through the last search afterwards
+    pub fn jump_to_problem(self, forward: bool) -> WindowState {
+        let problems: Vec<(i32, i32, i32)> = self
+            .lines
+            .iter()
+            .enumerate()
+            .filter_map(|(line, text)| match loglevel::detect(text) {
+                Some((level, start, end)) if level >= Level::Warn => {
+                    Some((line as i32, start as i32, end as i32))
+                }
+                _ => None,
+            })
+            .collect();
+        // Unlike a new search, skip a problem on the top line, since that's the one already being looked at
+        let (index, wrapped) = match next_result(&problems, None, self.content_top, forward, false)
+        {
+            Some(next) => next,
+            None => {
+                self.show_message("No WARN or ERROR lines");
+                return self;
+            }
+        };
+        self.show_message("");
+        let jump_line: i32 = problems[index].0;
+        let new_state: WindowState = self.jump_to_line(&jump_line);
+        if wrapped {
+            new_state.show_message(wrap_message(forward));
+        }
+        new_state
+    }
+
+    // Show a listing of the given directory, with the first entry selected
+    // If it can't be read, the error is shown in the message area and the current buffer stays on screen
+    pub fn browse(self, dir: &Path) -> WindowState {
+        match DirListing::read(dir) {
+            Ok(listing) => self.show_listing(listing),
+            Err(e) => {
+                self.show_message(&LoadError::from_io(dir, e).to_string());
+                self
+            }
+        }
+    }
+
+    fn show_listing(self, listing: DirListing) -> WindowState {
+        let lines: Vec<String> = listing.to_lines();
+        let mut new_state: WindowState = self.replace_lines(lines);
+        new_state.name = listing.dir.display().to_string();
+        new_state.path = None;
+        new_state.listing = Some(listing);
+        new_state.selected = 1;
+        new_state.jump_to_line(&0)
+    }
+
+    // Move the directory listing's selection to line n, scrolling if it would leave the screen
+    // Line 0 is the directory header, so it can't be selected
+    pub fn select_entry(self, n: i32) -> WindowState {
+        let mut new_state: WindowState = self;
+        new_state.selected = max(1, min(n, new_state.content_len - 1));
+        let new_top: i32 = if new_state.selected <= new_state.content_top {
+            // Keep the header visible when moving back up to the first entry
+            if new_state.selected == 1 {
+                0
+            } else {
+                new_state.selected
+            }
+        } else if new_state.selected >= new_state.content_top + new_state.pane_height {
+            new_state.selected - new_state.pane_height + 1
+        } else {
+            new_state.content_top
+        };
+        new_state.jump_to_line(&new_top)
+    }
+
+    // Open the selected entry of the directory listing, descending into it if it's a directory
+    // In global search results, open the selected match instead
+    pub fn open_selected_entry(self) -> WindowState {
+        if self.matches.is_some() {
+            return self.open_global_match();
+        }
+        let target = match &self.listing {
+            Some(listing) => listing
+                .entries
+                .get((self.selected - 1) as usize)
+                .map(|e| (e.path.clone(), e.is_dir)),
+            None => None,
+        };
+        match target {
+            Some((path, true)) => self.browse(&path),
+            Some((path, false)) => self.open_path(&path.to_string_lossy()),
+            None => self,
+        }
+    }
+
+    // Prompt for a term and search every open buffer for it (the one being viewed, other panes' and background ones),
+    // listing the matches grouped by buffer in a new buffer
+    pub fn global_search(self) -> WindowState {
+        self.show_message("Search all buffers:");
+        let term: String = match self.read_input(false) {
+            Some(term) if !term.is_empty() => term,
+            _ => {
+                self.show_message("");
+                return self;
+            }
+        };
+        // The buffer being viewed goes to the end of buffers once the list is opened in its place
+        let mut sources: Vec<(Source, &String, &Arc<Vec<String>>)> = Vec::new();
+        if !self.selecting() {
+            sources.push((
+                Source::Background(self.buffers.len()),
+                &self.name,
+                &self.lines,
+            ));
+        }
+        for id in self.layout.pane_ids() {
+            if let Some(pane) = self.panes.iter().find(|p| p.id == id) {
+                if pane.buffer.listing.is_none() && pane.buffer.matches.is_none() {
+                    sources.push((Source::Pane(id), &pane.buffer.name, &pane.buffer.lines));
+                }
+            }
+        }
+        for (index, buffer) in self.buffers.iter().enumerate() {
+            if buffer.listing.is_none() && buffer.matches.is_none() {
+                sources.push((Source::Background(index), &buffer.name, &buffer.lines));
+            }
+        }
+        // Each buffer is searched on worker threads the same way as run_search(), with progress across all of them shown
+        // in the message area, and Esc or Ctrl-C stops the search, listing the matches found so far
+        let total: usize = sources
+            .iter()
+            .map(|(_source, _name, lines)| lines.len())
+            .sum();
+        let mut searched: usize = 0;
+        let mut found: usize = 0;
+        let mut cancelled: bool = false;
+        // Keys typed ahead during the search, which are put back for the main loop once it's done
+        let mut typed: Vec<Input> = Vec::new();
+        let mut files: Vec<FileMatches> = Vec::new();
+        // Ctrl-C would kill least instead of coming through getch() outside of raw mode
+        self.window.set_raw(true);
+        self.window.nodelay(true);
+        for (source, name, lines) in sources {
+            let job: SearchJob = SearchJob::spawn(Arc::clone(lines), term.clone(), 0, false);
+            let mut results: Vec<(i32, i32, i32)> = Vec::new();
+            let mut chunks_in: usize = 0;
+            loop {
+                match job.chunks.recv_timeout(Duration::from_millis(50)) {
+                    Ok(chunk) => {
+                        searched += chunk.lines;
+                        found += chunk.results.len();
+                        chunks_in += 1;
+                        results.extend(chunk.results);
+                        // Done, so a key pressed since then is left for the next buffer or the main loop
+                        if chunks_in == job.chunk_count {
+                            break;
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => (),
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+                match self.window.getch() {
+                    Some(Input::Character('\u{1b}')) | Some(Input::Character('\u{3}')) => {
+                        job.cancel();
+                        cancelled = true;
+                        break;
+                    }
+                    Some(input) => typed.push(input),
+                    None => (),
+                }
+                self.show_message(&format!(
+                    "Searching {}... {}% ({} matches, Esc to cancel)",
+                    name,
+                    searched * 100 / max(total, 1),
+                    found
+                ));
+            }
+            // Chunks can come back out of order
+            results.sort_unstable();
+            if !results.is_empty() {
+                files.push(FileMatches {
+                    source,
+                    name: name.clone(),
+                    lines: Arc::clone(lines),
+                    results,
+                });
+            }
+            if cancelled {
+                break;
+            }
+        }
+        self.window.nodelay(false);
+        self.window.set_raw(false);
+        for input in typed.iter().rev() {
+            self.window.ungetch(input);
+        }
+        if files.is_empty() {
+            self.show_message(if cancelled {
+                "Search cancelled"
+            } else {
+                "Pattern not found in any buffer"
+            });
+            return self;
+        }
+        let (list, lines, highlights) = MatchList::new(term.clone(), files);
+        let mut buffer: Buffer = Buffer::new(format!("Search: {}", term), None, lines);
+        buffer.matches = Some(list);
+        buffer.search_results = highlights;
+        buffer.selected = 1;
+        self.show_message("");
+        let new_state: WindowState = self.open_buffer(buffer);
+        if cancelled {
+            new_state.show_message(&format!(
+                "Search cancelled at {}% ({} matches so far)",
+                searched * 100 / max(total, 1),
+                found
+            ));
+        }
+        new_state
+    }
+
+    // Open the buffer that the selected line of the global search results points at, scrolled to the match,
+    // with that buffer's matches as its search results so they're highlighted and n and N step through them
+    fn open_global_match(self) -> WindowState {
+        let (source, name, line, results, term) = match &self.matches {
+            Some(list) => match list.target(self.selected) {
+                Some((file, line)) => (
+                    file.source,
+                    file.name.clone(),
+                    line,
+                    file.results.clone(),
+                    list.term.clone(),
+                ),
+                None => return self,
+            },
+            None => return self,
+        };
+        let in_pane: bool = match source {
+            Source::Pane(id) => self
+                .panes
+                .iter()
+                .any(|p| p.id == id && p.buffer.name == name),
+            Source::Background(_) => false,
+        };
+        let mut new_state: WindowState = if let (true, Source::Pane(id)) = (in_pane, source) {
+            self.focus_pane(id)
+        } else {
+            // Background buffers can be reordered with [ and ] after the search, so fall back to finding it by name
+            let index: Option<usize> = match source {
+                Source::Background(index)
+                    if self.buffers.get(index).is_some_and(|b| b.name == name) =>
+                {
+                    Some(index)
+                }
+                _ => self.buffers.iter().position(|b| b.name == name),
+            };
+            match index {
+                Some(index) => {
+                    let mut new_state: WindowState = self;
+                    let buffer: Buffer = new_state.buffers.remove(index);
+                    new_state.open_buffer(buffer)
+                }
+                None => {
+                    self.show_message(&format!("\"{}\" is no longer open", name));
+                    return self;
+                }
+            }
+        };
+        // The buffer can have changed since the search (ex: folded in JSON mode), so only keep the matches still there
+        let lines: &Arc<Vec<String>> = &new_state.lines;
+        let results: Vec<(i32, i32, i32)> = results
+            .into_iter()
+            .filter(|r| {
+                lines
+                    .get(r.0 as usize)
+                    .and_then(|l| l.get(r.1 as usize..r.2 as usize))
+                    == Some(term.as_str())
+            })
+            .collect();
+        new_state.search_index = results.iter().position(|r| r.0 == line);
+        new_state.search_results = results;
+        if let Some(table) = new_state.table.as_mut() {
+            table.end_column_search();
+        }
+        new_state.search_reverse = false;
+        new_state.search_hidden = false;
+        new_state.jump_to_line(&line)
+    }
+
+    // Go up from the directory being browsed to its parent
+    pub fn browse_parent(self) -> WindowState {
+        let parent = self
+            .listing
+            .as_ref()
+            .and_then(|l| l.dir.parent().map(|p| p.to_path_buf()));
+        match parent {
+            Some(parent) => self.browse(&parent),
+            None => self,
+        }
+    }
+
+    // Draw the prompt's input in the input section, scrolled so that the cursor is visible, and place the cursor
+    fn draw_prompt(&self, editor: &mut LineEditor, input_window_size: i32) {
+        let input_left: i32 = self.screen_width - input_window_size;
+        self.window.mv(self.screen_height, input_left);
+        self.window.clrtoeol();
+        let (visible, cursor_col) = editor.view(input_window_size as usize);
+        self.window.addstr(&visible);
+        self.window
+            .mv(self.screen_height, input_left + cursor_col as i32);
+        self.window.refresh();
+    }
+
+    // Read the rest of an escape sequence after an Esc has been received
+    // Returns None if nothing follows the Esc, aka the user actually pressed the Esc key
+    fn read_escape_sequence(&self) -> Option<String> {
+        self.window.nodelay(true);
+        let mut sequence: String = String::new();
+        while let Some(Input::Character(c)) = self.window.getch() {
+            sequence.push(c);
+            // CSI sequences (Esc [ ...) end with a char in the range @ to ~, everything else is just Alt + key
+            if !sequence.starts_with('[') || (sequence.len() > 1 && ('@'..='~').contains(&c)) {
+                break;
+            }
+        }
+        self.window.nodelay(false);
+        if sequence.is_empty() {
+            None
+        } else {
+            Some(sequence)
+        }
+    }
+
+    // Collect bracketed paste input until the terminal sends the end of paste sequence (Esc [ 2 0 1 ~)
+    // Prompts are a single line, so pasted newlines and tabs are flattened into spaces
+    fn read_paste(&self) -> String {
+        let mut pasted: String = String::new();
+        while !pasted.ends_with("\u{1b}[201~") {
+            match self.window.getch() {
+                Some(Input::Character('\n'))
+                | Some(Input::Character('\r'))
+                | Some(Input::Character('\t')) => pasted.push(' '),
+                Some(Input::Character(c)) => pasted.push(c),
+                None if self.window.closed() => break,
+                _ => (),
+            }
+        }
+        match pasted.strip_suffix("\u{1b}[201~") {
+            Some(text) => text.to_string(),
+            None => pasted,
+        }
+    }
+
+    // Move the cursor to the input section (bottom right) and let the user edit a line of input
+    // If complete_paths is true, Tab completes the path typed so far, cycling through the candidates when there's more than one
+    // Returns the input once the user presses Enter, or None if they cancel with Esc
+    fn read_input(&self, complete_paths: bool) -> Option<String> {
+        let input_window_size: i32 = min(20, self.screen_width);
+        let mut editor: LineEditor = LineEditor::new();
+        // Candidates offered by the last Tab, and which one is filled in once the user starts cycling
+        let mut completions: Vec<String> = Vec::new();
+        let mut completion_index: Option<usize> = None;
+        // Ask the terminal to wrap pasted text in escape sequences, so pasting doesn't trigger any key bindings
+        self.window.set_bracketed_paste(true);
+        self.draw_prompt(&mut editor, input_window_size);
+        let result: Option<String> = loop {
+            let input: Option<Input> = self.window.getch();
+            if input != Some(Input::Character('\t')) && !completions.is_empty() {
+                // Any key other than Tab accepts the current completion, so take the popup off the screen
+                completions.clear();
+                completion_index = None;
+                self.draw_screen();
+            }
+            match input {
+                Some(Input::Character('\n')) => break Some(editor.text()),
+                Some(Input::Character('\u{1b}')) => match self.read_escape_sequence() {
+                    None => break None,
+                    Some(sequence) if sequence == "[200~" => editor.insert_str(&self.read_paste()),
+                    Some(_sequence) => (),
+                },
+                Some(Input::Character('\t')) if complete_paths => {
+                    if completions.is_empty() {
+                        completions = complete_path(&editor.text());
+                        match completions.len() {
+                            0 => {
+                                self.window.beep();
+                            }
+                            1 => {
+                                editor.set_text(&completions.remove(0));
+                            }
+                            _ => {
+                                // First extend the input as far as all candidates agree, then start cycling on the next Tab
+                                let prefix: String = common_prefix(&completions);
+                                if prefix.len() > editor.text().len() {
+                                    editor.set_text(&prefix);
+                                } else {
+                                    completion_index = Some(0);
+                                    editor.set_text(&completions[0]);
+                                }
+                            }
+                        }
+                    } else {
+                        let next: usize =
+                            completion_index.map_or(0, |i| (i + 1) % completions.len());
+                        completion_index = Some(next);
+                        editor.set_text(&completions[next]);
+                    }
+                    self.draw_screen();
+                    if !completions.is_empty() {
+                        self.draw_completion_popup(&completions, completion_index);
+                    }
+                }
+                Some(input) => {
+                    editor.handle(&input);
+                }
+                None if self.window.closed() => break None,
+                None => (),
+            }
+            self.draw_prompt(&mut editor, input_window_size);
+        };
+        self.window.set_bracketed_paste(false);
+        self.window
+            .mv(self.screen_height, self.screen_width - input_window_size);
+        self.window.clrtoeol();
+        self.window.refresh();
+        result
+    }
+
+    // Draw the list of tab completion candidates in a box directly above the input section
+    // Only as many rows as fit are drawn, scrolled so that the selected candidate is always visible
+    fn draw_completion_popup(&self, candidates: &[String], selected: Option<usize>) {
+        let rows: i32 = min(candidates.len() as i32, min(10, self.screen_height));
+        let first: i32 = match selected {
+            Some(s) if s as i32 >= rows => s as i32 - rows + 1,
+            _ => 0,
+        };
+        let name_width: i32 = candidates
+            .iter()
+            .map(|c| display_name(c).chars().count() as i32)
+            .max()
+            .unwrap_or(0);
+        let popup_width: i32 = min(name_width + 2, self.screen_width + 1);
+        let popup_left: i32 = self.screen_width + 1 - popup_width;
+        for row in 0..rows {
+            let index: usize = (first + row) as usize;
+            let name: String = display_name(&candidates[index])
+                .chars()
+                .take((popup_width - 2).max(0) as usize)
+                .collect();
+            if selected == Some(index) {
+                self.window.attrset(backend::A_NORMAL, 2);
+            } else {
+                self.window.attrset(backend::A_REVERSE, 0);
+            }
+            self.window.mvaddstr(
+                self.screen_height - rows + row,
+                popup_left,
+                &format!(
+                    " {:<width$} ",
+                    name,
+                    width = (popup_width - 2).max(0) as usize
+                ),
+            );
+        }
+        self.window.attrset(backend::A_NORMAL, 1);
+        self.window.refresh();
+    }
+
+    // Show the help menu over the current file, with its own scroll position
+    // The file's lines, position and search results are left alone, so dismissing the help returns to exactly where you were
+    pub fn help_menu(self) -> WindowState {
+        let help_lines: Vec<&str> = HELP_MESSAGE.lines().collect();
+        let max_top: i32 = max(0, help_lines.len() as i32 - self.screen_height);
+        let mut help_top: i32 = 0;
+        loop {
+            self.window.clear();
+            for (row, line) in help_lines
+                .iter()
+                .skip(help_top as usize)
+                .take(self.screen_height as usize)
+                .enumerate()
+            {
+                self.window
+                    .mvaddnstr(row as i32, 0, line, self.screen_width + 1);
+            }
+            self.window
+                .mvaddstr(self.screen_height, 0, "Help - q, h or Esc to return");
+            self.window.refresh();
+            match self.window.getch() {
+                Some(Input::Character('q')) | Some(Input::Character('h')) => break,
+                // Esc on its own, rather than the start of a key sequence
+                Some(Input::Character('\u{1b}')) if self.read_escape_sequence().is_none() => break,
+                Some(Input::Character('j')) | Some(Input::KeyDown) => {
+                    help_top = min(help_top + 1, max_top);
+                }
+                Some(Input::Character('k')) | Some(Input::KeyUp) => {
+                    help_top = max(0, help_top - 1);
+                }
+                Some(Input::Character('d')) | Some(Input::KeyNPage) => {
+                    help_top = min(help_top + self.screen_height / 2, max_top);
+                }
+                Some(Input::Character('u')) | Some(Input::KeyPPage) => {
+                    help_top = max(0, help_top - self.screen_height / 2);
+                }
+                Some(Input::Character('g')) => {
+                    help_top = 0;
+                }
+                Some(_input) => (),
+                None if self.window.closed() => break,
+                None => (),
+            }
+        }
+        self.window.mv(self.screen_height, 0);
+        self.window.clrtoeol();
+        self.draw_screen();
+        self
+    }
+
+    // Prompt for a path (with Tab completion) and open it in a new buffer, see open_path()
+    pub fn open_file(self) -> WindowState {
+        match self.read_input(true) {
+            // Read it before putting the current buffer away, so a path that can't be opened leaves it on screen
+            Some(input_str) => match read_path(&input_str) {
+                Ok(contents) => {
+                    let mut new_state: WindowState = self;
+                    let current: Buffer = new_state.take_buffer();
+                    new_state.buffers.push(current);
+                    new_state.show_path(&input_str, contents)
+                }
+                Err(e) => {
+                    self.show_message(&e.to_string());
+                    self
+                }
+            },
+            None => self,
+        }
+    }
+
+    // Line of the file that a line of the buffer shows, which differs when it's shown as JSON, a table or a filtered log
+    fn original_line(&self, line: i32) -> usize {
+        if let Some(view) = &self.json {
+            view.original_line(line)
+        } else if let Some(table) = &self.table {
+            table.original_line(line)
+        } else if let Some(view) = &self.log {
+            view.original_line(line)
+        } else {
+            line as usize
+        }
+    }
+
+    // The first line of the buffer that shows a line of the file at or after the given one, see original_line()
+    fn shown_line(&self, original: usize) -> i32 {
+        if let Some(view) = &self.json {
+            view.shown_line(original)
+        } else if let Some(table) = &self.table {
+            table.shown_line(original)
+        } else if let Some(view) = &self.log {
+            view.shown_line(original)
+        } else {
+            original as i32
+        }
+    }
+
+    // Suspend curses and open the current file in the user's editor at the top line on the screen
+    // Once the editor exits, the file is reloaded and the view goes back to the same line (with the same log filter)
+    pub fn edit_file(self) -> WindowState {
+        let path: PathBuf = match &self.path {
+            Some(path) => path.clone(),
+            None => {
+                self.show_message("Can't edit: this buffer isn't a file on disk");
+                return self;
+            }
+        };
+        let file_top: usize = self.original_line(self.content_top);
+        self.window.endwin();
+        let status = editor_command(&path, file_top as i32 + 1).status();
+        // Refreshing after endwin() puts the terminal back into curses mode
+        self.window.refresh();
+        if let Err(e) = status {
+            let top: i32 = self.content_top;
+            let new_state: WindowState = self.jump_to_line(&top);
+            new_state.show_message(&format!("Could not start editor: {}", e));
+            return new_state;
+        }
+        let top: i32 = self.content_top;
+        let min_level: Option<Level> = self.log.as_ref().and_then(|view| view.min_level);
+        let lines: Vec<String> = match read_lines(&path) {
+            Ok(lines) => lines,
+            Err(e) => {
+                // Ex: the file was deleted from the editor, so keep showing it the way it was
+                let new_state: WindowState = self.jump_to_line(&top);
+                new_state.show_message(&format!("Could not reload: {}", e));
+                return new_state;
+            }
+        };
+        let name: String = self.name.clone();
+        let mark: Option<i32> = self.mark;
+        let mut new_state: WindowState = self.replace_lines(lines);
+        new_state.name = name;
+        new_state.path = Some(path);
+        new_state.mark = mark.filter(|m| *m < new_state.content_len);
+        new_state = new_state.detect_json().detect_table().detect_log();
+        if new_state.log.is_some() && min_level.is_some() {
+            new_state = new_state.set_min_level(min_level);
+        }
+        let new_top: i32 = min(
+            new_state.shown_line(file_top),
+            max(0, new_state.content_len - 1),
+        );
+        new_state.jump_to_line(&new_top)
+    }
+
+    // Ask which lines to save (see choose_line_range()) and save them, see save_lines()
+    pub fn save_to_file(self) -> WindowState {
+        match self.choose_line_range("Save") {
+            Some(range) => self.save_lines(range),
+            None => self,
+        }
+    }
+
+    // Ask for a path (with Tab completion) and save the lines in [start, end) to it
+    // Asks before overwriting an existing file, and reports how it went in the message area
+    pub fn save_lines(self, range: (i32, i32)) -> WindowState {
+        let selected_lines: &[String] = self.lines_in_range(range);
+        self.show_message(&format!("Save {} lines to:", selected_lines.len()));
+        let filename: String = match self.read_input(true) {
+            Some(filename) if !filename.is_empty() => filename,
+            _ => {
+                self.show_message("");
+                return self;
+            }
+        };
+        let expanded_filename: String = match expand_path(&filename) {
+            Ok(expanded) => expanded,
+            Err(e) => {
+                self.show_message(&e.to_string());
+                return self;
+            }
+        };
+        if Path::new(&expanded_filename).exists() {
+            self.show_message(&format!(
+                "\"{}\" already exists, overwrite? (y/n)",
+                filename
+            ));
+            if self.window.getch() != Some(Input::Character('y')) {
+                self.show_message("Not saved");
+                return self;
+            }
+        }
+        let contents: String = selected_lines.iter().map(|l| format!("{}\n", l)).collect();
+        match fs::write(&expanded_filename, contents) {
+            Ok(()) => self.show_message(&format!(
+                "Wrote {} lines to \"{}\"",
+                selected_lines.len(),
+                filename
+            )),
+            Err(e) => self.show_message(&format!("Could not write \"{}\": {}", filename, e)),
+        }
+        self
+    }
+
+    // The [start, end) range of lines selected in visual mode, if it's active
+    fn visual_range(&self) -> Option<(i32, i32)> {
+        self.visual
+            .map(|(anchor, cursor)| (min(anchor, cursor), max(anchor, cursor) + 1))
+    }
+
+    // The lines in the [start, end) range, cut down to the lines the buffer has
+    // (ex: the screen range once d has scrolled the last line off the top)
+    fn lines_in_range(&self, range: (i32, i32)) -> &[String] {
+        let end: usize = min(max(0, range.1) as usize, self.lines.len());
+        let start: usize = min(max(0, range.0) as usize, end);
+        &self.lines[start..end]
+    }
+
+    // Start selecting lines in visual mode, from the current line (see current_line())
+    pub fn start_visual(self) -> WindowState {
+        if self.content_len == 0 {
+            self.show_message("Nothing to select");
+            return self;
+        }
+        let mut new_state: WindowState = self;
+        // The current line can be past the end after d scrolls the last line off the top
+        let here: i32 = min(new_state.current_line(), new_state.content_len - 1);
+        new_state.visual = Some((here, here));
+        new_state.move_visual(here)
+    }
+
+    // Move the moving end of the visual selection to line n, scrolling if it would leave the screen
+    pub fn move_visual(self, n: i32) -> WindowState {
+        let anchor: i32 = match self.visual {
+            Some((anchor, _cursor)) => max(0, min(anchor, self.content_len - 1)),
+            None => return self,
+        };
+        let mut new_state: WindowState = self;
+        let cursor: i32 = max(0, min(n, new_state.content_len - 1));
+        new_state.visual = Some((anchor, cursor));
+        let new_top: i32 = if cursor < new_state.content_top {
+            cursor
+        } else if cursor >= new_state.content_top + new_state.pane_height {
+            cursor - new_state.pane_height + 1
+        } else {
+            new_state.content_top
+        };
+        new_state = new_state.jump_to_line(&new_top);
+        new_state.show_message(&format!(
+            "-- VISUAL LINE -- {} lines (y copy, s save, | pipe, Esc cancel)",
+            (anchor - cursor).abs() + 1
+        ));
+        new_state
+    }
+
+    // Leave visual mode, dropping the selection
+    pub fn end_visual(self) -> WindowState {
+        let mut new_state: WindowState = self;
+        new_state.visual = None;
+        let top: i32 = new_state.content_top;
+        new_state.jump_to_line(&top)
+    }
+
+    // Copy the visual selection to the clipboard and leave visual mode
+    // Uses OSC 52 unless $LEAST_YANK_COMMAND is set, in which case the lines are piped to that command instead (ex: xclip)
+    pub fn yank_selection(self) -> WindowState {
+        let range: (i32, i32) = match self.visual_range() {
+            Some(range) => range,
+            None => return self,
+        };
+        let selected_lines: &[String] = self.lines_in_range(range);
+        let result: Result<(), String> = match env::var("LEAST_YANK_COMMAND") {
+            Ok(command) if !command.trim().is_empty() => match pipe_lines(&command, selected_lines)
+            {
+                Ok(output) if output.success => Ok(()),
+                Ok(output) => Err(output.stderr.first().cloned().unwrap_or(output.status)),
+                Err(e) => Err(e.to_string()),
+            },
+            _ => {
+                let text: String = selected_lines.iter().map(|l| format!("{}\n", l)).collect();
+                copy_osc52(&text).map_err(|e| e.to_string())
+            }
+        };
+        let line_count: usize = selected_lines.len();
+        let new_state: WindowState = self.end_visual();
+        match result {
+            Ok(()) => new_state.show_message(&format!("Copied {} lines", line_count)),
+            Err(e) => new_state.show_message(&format!("Could not copy: {}", e)),
+        }
+        new_state
+    }
+
+    // Set the mark at the current line, see current_line()
+    pub fn set_mark(self) -> WindowState {
+        let mut new_state: WindowState = self;
+        new_state.mark = Some(new_state.current_line());
+        new_state.show_message(&format!(
+            "Mark set at line {}",
+            new_state.current_line() + 1
+        ));
+        new_state
+    }
+
+    // Ask which lines a command should act on: the screen, the whole buffer, or from the mark to the current line
+    // Returns the [start, end) range of lines, or None if the user cancelled (or there's no mark to use)
+    fn choose_line_range(&self, verb: &str) -> Option<(i32, i32)> {
+        self.show_message(&format!(
+            "{} [s]creen, [b]uffer or [m]ark to current line?",
+            verb
+        ));
+        match self.window.getch() {
+            Some(Input::Character('s')) => Some((self.content_top, self.content_bottom)),
+            Some(Input::Character('b')) => Some((0, self.content_len)),
+            Some(Input::Character('m')) => match self.mark {
+                Some(mark) => {
+                    let here: i32 = self.current_line();
+                    let last_line: i32 = self.content_len - 1;
+                    Some((min(mark, here), min(max(mark, here), last_line) + 1))
+                }
+                None => {
+                    self.show_message("No mark set, press m to set one");
+                    None
+                }
+            },
+            _ => {
+                self.show_message("");
+                None
+            }
+        }
+    }
+
+    // Ask which lines to pipe (see choose_line_range()) and pipe them, see pipe_lines_to_command()
+    pub fn pipe_to_command(self) -> WindowState {
+        match self.choose_line_range("Pipe") {
+            Some(range) => self.pipe_lines_to_command(range),
+            None => self,
+        }
+    }
+
+    // Ask for a shell command and pipe the lines in [start, end) to it
+    // Output that fits on one line is shown in the message area, anything longer is opened in a new buffer
+    pub fn pipe_lines_to_command(self, range: (i32, i32)) -> WindowState {
+        let selected_lines: &[String] = self.lines_in_range(range);
+        self.show_message(&format!("Pipe {} lines to:", selected_lines.len()));
+        let command: String = match self.read_input(false) {
+            Some(command) => command,
+            None => {
+                self.show_message("");
+                return self;
+            }
+        };
+        match pipe_lines(&command, selected_lines) {
+            Ok(output) => {
+                if !output.success && output.stdout.is_empty() {
+                    let reason: String = output.stderr.first().cloned().unwrap_or(output.status);
+                    self.show_message(&format!("{}: {}", command, reason));
+                    self
+                } else if output.stdout.len() > 1 {
+                    let buffer: Buffer = Buffer::new(format!("| {}", command), None, output.stdout);
+                    self.open_buffer(buffer)
+                } else {
+                    let first_line: String = output
+                        .stdout
+                        .first()
+                        .or_else(|| output.stderr.first())
+                        .cloned()
+                        .unwrap_or_else(|| String::from("(no output)"));
+                    self.show_message(&first_line);
+                    self
+                }
+            }
+            Err(e) => {
+                self.show_message(&format!("Could not run \"{}\": {}", command, e));
+                self
+            }
+        }
+    }
+
+    // Ask for a pattern to keep highlighted in every pane, in the next free color
+    pub fn pin_pattern(self) -> WindowState {
+        let color: usize = match free_color(&self.pins) {
+            Some(color) => color,
+            None => {
+                self.show_message(&format!(
+                    "All {} highlight colors are in use, press P to remove a pin",
+                    PIN_COLORS.len()
+                ));
+                return self;
+            }
+        };
+        self.show_message("Pin pattern:");
+        match self.read_input(false) {
+            Some(pattern) if !pattern.is_empty() => {
+                let mut new_state: WindowState = self;
+                new_state.show_message(&format!(
+                    "Pinned \"{}\" in {}",
+                    pattern, PIN_COLORS[color].2
+                ));
+                new_state.pins.push(Pin { pattern, color });
+                new_state.draw_screen();
+                new_state
+            }
+            _ => {
+                self.show_message("");
+                self
+            }
+        }
+    }
+
+    // List the pinned patterns in their colors above the message area, and let the user remove one or all of them
+    pub fn manage_pins(self) -> WindowState {
+        if self.pins.is_empty() {
+            self.show_message("No pinned patterns, press p to pin one");
+            return self;
+        }
+        let rows: usize = min(self.pins.len(), self.screen_height as usize);
+        for (i, pin) in self.pins.iter().enumerate().take(rows) {
+            let (pair, _background, color_name) = PIN_COLORS[pin.color];
+            self.window.attrset(backend::A_NORMAL, pair);
+            self.window.mvaddnstr(
+                self.screen_height - rows as i32 + i as i32,
+                0,
+                &format!(" {} {} ({}) ", i + 1, pin.pattern, color_name),
+                self.screen_width + 1,
+            );
+        }
+        self.window.attrset(backend::A_NORMAL, 1);
+        self.show_message(&format!(
+            "Remove pin [1-{}], [a]ll, or any other key to keep them",
+            self.pins.len()
+        ));
+        let input: Option<Input> = self.window.getch();
+        let mut new_state: WindowState = self;
+        let message: String = match input {
+            Some(Input::Character('a')) => {
+                new_state.pins.clear();
+                String::from("Removed all pins")
+            }
+            Some(Input::Character(c))
+                if c.to_digit(10)
+                    .is_some_and(|n| n >= 1 && n as usize <= new_state.pins.len()) =>
+            {
+                let index: usize = c.to_digit(10).unwrap() as usize - 1;
+                let removed: Pin = new_state.pins.remove(index);
+                format!("Removed \"{}\"", removed.pattern)
+            }
+            _ => String::new(),
+        };
+        new_state.draw_screen();
+        new_state.show_message(&message);
+        new_state
+    }
+
+    // Take user input and search for it, see run_search()
+    // If reverse is true, searches upwards from the top line, and n and N are swapped until the next search
+    pub fn search(self, reverse: bool) -> WindowState {
+        match self.read_input(false) {
+            Some(input_str) => self.run_search(input_str, reverse),
+            None => self,
+        }
+    }
+
+    // Search for a term on a worker thread, jumping to the first match from the top line as soon as it's found
+    // Progress is shown in the message area while the rest of the file is searched, and Esc or Ctrl-C stops the search,
+    // keeping the matches found so far
+    fn run_search(self, term: String, reverse: bool) -> WindowState {
+        let mut new_state: WindowState = self;
+        if let Some(table) = new_state.table.as_mut() {
+            table.end_column_search();
+        }
+        new_state.search_results = Vec::new();
+        new_state.search_index = None;
+        new_state.search_reverse = reverse;
+        new_state.search_hidden = false;
+        let total: usize = new_state.lines.len();
+        let start: usize = max(0, new_state.content_top) as usize;
+        let job: SearchJob = SearchJob::spawn(Arc::clone(&new_state.lines), term, start, reverse);
+        let mut searched: usize = 0;
+        let mut wrapped: bool = false;
+        // Which chunks are in and whether they had matches, how many of the first ones in search order are all in,
+        // and whether any of those had matches
+        let mut finished: Vec<Option<bool>> = vec![None; job.chunk_count];
+        let mut finished_in_order: usize = 0;
+        let mut found_in_order: bool = false;
+        // Keys typed ahead during the search, which are put back for the main loop once it's done
+        let mut typed: Vec<Input> = Vec::new();
+        // Ctrl-C would kill least instead of coming through getch() outside of raw mode
+        new_state.window.set_raw(true);
+        new_state.window.nodelay(true);
+        let cancelled: bool = loop {
+            match job.chunks.recv_timeout(Duration::from_millis(50)) {
+                Ok(chunk) => {
+                    searched += chunk.lines;
+                    finished[chunk.index] = Some(!chunk.results.is_empty());
+                    // Chunks cover separate runs of lines, so each one's matches slot in as a block
+                    if let Some(&(first_line, _start, _end)) = chunk.results.first() {
+                        let found: usize = chunk.results.len();
+                        let at: usize = new_state
+                            .search_results
+                            .partition_point(|r| r.0 < first_line);
+                        new_state.search_results.splice(at..at, chunk.results);
+                        if let Some(index) = new_state.search_index {
+                            if index >= at {
+                                new_state.search_index = Some(index + found);
+                            }
+                            new_state.draw_lines(new_state.content_top, new_state.content_bottom);
+                        }
+                    }
+                    // Jump once a match turns up with every chunk before it in, since it must be the nearest one
+                    while let Some(Some(had_matches)) = finished.get(finished_in_order) {
+                        found_in_order |= *had_matches;
+                        finished_in_order += 1;
+                    }
+                    if new_state.search_index.is_none() && found_in_order {
+                        wrapped = new_state
+                            .next_search_index(!reverse, true)
+                            .is_some_and(|(_index, wrapped)| wrapped);
+                        new_state = new_state.step_search(!reverse, true);
+                    }
+                    // Done, so a key pressed since then is left for the main loop rather than cancelling
+                    if finished_in_order == job.chunk_count {
+                        break false;
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => break false,
+            }
+            match new_state.window.getch() {
+                Some(Input::Character('\u{1b}')) | Some(Input::Character('\u{3}')) => {
+                    job.cancel();
+                    break true;
+                }
+                Some(input) => typed.push(input),
+                None => (),
+            }
+            new_state.show_message(&format!(
+                "Searching... {}% ({} matches, Esc to cancel)",
+                searched * 100 / max(total, 1),
+                new_state.search_results.len()
+            ));
+        };
+        new_state.window.nodelay(false);
+        new_state.window.set_raw(false);
+        for input in typed.iter().rev() {
+            new_state.window.ungetch(input);
+        }
+        if new_state.search_results.is_empty() {
+            new_state.show_message(if cancelled {
+                "Search cancelled"
+            } else {
+                "Pattern not found"
+            });
+            return new_state;
+        }
+        // Put back whatever the jump showed for the line (ex: its timestamp) in place of the progress
+        new_state.show_message("");
+        let top: i32 = new_state.content_top;
+        new_state = new_state.jump_to_line(&top);
+        if cancelled {
+            new_state.show_message(&format!(
+                "Search cancelled at {}% ({} matches so far)",
+                searched * 100 / max(total, 1),
+                new_state.search_results.len()
+            ));
+        } else if wrapped {
+            new_state.show_message(wrap_message(!reverse));
+        }
+        new_state
+    }
+
+    // Make a new set of matches the search results and jump to the first one from the top line, which counts itself
+    fn start_search(self, search_results: Vec<(i32, i32, i32)>, reverse: bool) -> WindowState {
+        let mut new_state: WindowState = self;
+        new_state.search_results = search_results;
+        new_state.search_index = None;
+        new_state.search_reverse = reverse;
+        new_state.step_search(!reverse, true)
+    }
+
+    // Move the screen to the next search result in the direction of the last search
+    pub fn jump_to_next_search_result(self) -> WindowState {
+        let forward: bool = !self.search_reverse;
+        self.step_search(forward, false)
+    }
+
+    // Move the screen to the previous search result in the direction of the last search
+    pub fn jump_to_last_search_result(self) -> WindowState {
+        let forward: bool = self.search_reverse;
+        self.step_search(forward, false)
+    }
+
+    // Index of the search result after (or before) the current position, see next_result()
+    fn next_search_index(&self, forward: bool, include_top: bool) -> Option<(usize, bool)> {
+        next_result(
+            &self.search_results,
+            self.search_index,
+            self.content_top,
+            forward,
+            include_top,
+        )
+    }
+
+    // Move the screen to the line of the next (or previous) search result, saying so if it wrapped around the file
+    fn step_search(self, forward: bool, include_top: bool) -> WindowState {
+        let (index, wrapped) = match self.next_search_index(forward, include_top) {
+            Some(next) => next,
+            None => {
+                self.show_message("Pattern not found");
+                return self;
+            }
+        };
+        // Clear an earlier wrap notice, jumping can put up its own message (ex: the line's timestamp)
+        self.show_message("");
+        let jump_line: i32 = self.search_results[index].0;
+        let mut new_state: WindowState = self;
+        new_state.search_index = Some(index);
+        new_state.search_hidden = false;
+        new_state = new_state.jump_to_line(&jump_line);
+        if wrapped {
+            new_state.show_message(wrap_message(forward));
+        }
+        new_state
+    }
+
+    // Stop highlighting the search results until the next search or jump to one (n and N still work)
+    pub fn clear_search_highlight(self) -> WindowState {
+        let mut new_state: WindowState = self;
+        new_state.search_hidden = true;
+        let top: i32 = new_state.content_top;
+        new_state.jump_to_line(&top)
+    }
+}
+
+// Index of the result (ex: a search match) after (or before) the current position, and whether it had to wrap around the end of the file
+// The current position is the result last jumped to if it's still on the top line, so n steps through matches on the same line,
+// otherwise it's the top line itself, so n after scrolling finds the next match from where the screen is now
+// With include_top, a match on the top line counts as the next one (ex: for a new search)
+fn next_result(
+    results: &[(i32, i32, i32)],
+    last: Option<usize>,
+    top: i32,
+    forward: bool,
+    include_top: bool,
+) -> Option<(usize, bool)> {
+    if results.is_empty() {
+        return None;
+    }
+    let current: Option<usize> = last.filter(|i| results.get(*i).is_some_and(|r| r.0 == top));
+    if forward {
+        let next: usize = match current {
+            Some(i) => i + 1,
+            None if include_top => results.partition_point(|r| r.0 < top),
+            None => results.partition_point(|r| r.0 <= top),
+        };
+        if next < results.len() {
+            Some((next, false))
+        } else {
+            Some((0, true))
+        }
+    } else {
+        let after: usize = match current {
+            Some(i) => i,
+            None if include_top => results.partition_point(|r| r.0 <= top),
+            None => results.partition_point(|r| r.0 < top),
+        };
+        match after.checked_sub(1) {
+            Some(previous) => Some((previous, false)),
+            None => Some((results.len() - 1, true)),
+        }
+    }
+}
+
+// What to say when jumping to the next search result wraps around the end of the file
+fn wrap_message(forward: bool) -> &'static str {
+    if forward {
+        "search hit BOTTOM, continuing at TOP"
+    } else {
+        "search hit TOP, continuing at BOTTOM"
+    }
+}
+
+// Main program logic: handle keys until the user quits, then give the terminal back
+pub fn run(mut state: WindowState) {
+    let init_pos: i32 = 0;
+
+    // Main control loop
+    loop {
+        let input: Option<Input> = state.window.getch();
+        // In visual mode, movement keys extend the selection and the other keys act on it
+        if let Some((_anchor, cursor)) = state.visual {
+            match input {
+                Some(Input::Character('j')) | Some(Input::KeyDown) => {
+                    state = state.move_visual(cursor + 1);
+                }
+                Some(Input::Character('k')) | Some(Input::KeyUp) => {
+                    state = state.move_visual(cursor - 1);
+                }
+                Some(Input::Character('d')) | Some(Input::KeyNPage) => {
+                    let new_cursor: i32 = cursor + state.pane_height / 2;
+                    state = state.move_visual(new_cursor);
+                }
+                Some(Input::Character('u')) | Some(Input::KeyPPage) => {
+                    let new_cursor: i32 = cursor - state.pane_height / 2;
+                    state = state.move_visual(new_cursor);
+                }
+                Some(Input::Character('g')) => {
+                    state = state.move_visual(0);
+                }
+                // y - Copy the selection to the clipboard
+                Some(Input::Character('y')) => {
+                    state = state.yank_selection();
+                }
+                // s, | - Save the selection to a file or pipe it to a command
+                Some(Input::Character('s')) | Some(Input::Character('|')) => {
+                    let range: (i32, i32) = state.visual_range().unwrap();
+                    state = state.end_visual();
+                    state = if input == Some(Input::Character('s')) {
+                        state.save_lines(range)
+                    } else {
+                        state.pipe_lines_to_command(range)
+                    };
+                }
+                // V, q, Esc - Leave visual mode
+                Some(Input::Character('V')) | Some(Input::Character('q')) => {
+                    state = state.end_visual();
+                }
+                Some(Input::Character('\u{1b}')) if state.read_escape_sequence().is_none() => {
+                    state = state.end_visual();
+                }
+                None if state.window.closed() => break,
+                _ => (),
+            }
+            continue;
+        }
+        // While browsing a directory or global search results, movement keys move the selection instead of just scrolling
+        if state.selecting() {
+            let handled: bool = match input {
+                Some(Input::Character('j')) | Some(Input::KeyDown) => {
+                    let new_selection: i32 = state.selected + 1;
+                    state = state.select_entry(new_selection);
+                    true
+                }
+                Some(Input::Character('k')) | Some(Input::KeyUp) => {
+                    let new_selection: i32 = state.selected - 1;
+                    state = state.select_entry(new_selection);
+                    true
+                }
+                Some(Input::Character('d')) | Some(Input::KeyNPage) => {
+                    let new_selection: i32 = state.selected + state.pane_height / 2;
+                    state = state.select_entry(new_selection);
+                    true
+                }
+                Some(Input::Character('u')) | Some(Input::KeyPPage) => {
+                    let new_selection: i32 = state.selected - state.pane_height / 2;
+                    state = state.select_entry(new_selection);
+                    true
+                }
+                Some(Input::Character('g')) => {
+                    state = state.select_entry(1);
+                    true
+                }
+                // Enter - Open the selected file or directory, or the selected match
+                Some(Input::Character('\n')) => {
+                    state = state.open_selected_entry();
+                    true
+                }
+                // -, Backspace - Go up to the parent directory
+                Some(Input::Character('-'))
+                | Some(Input::KeyBackspace)
+                | Some(Input::Character('\u{7f}')) => {
+                    state = state.browse_parent();
+                    true
+                }
+                _ => false,
+            };
+            if handled {
+                continue;
+            }
+        }
+        match input {
+            // q - Quit
+            Some(Input::Character('q')) => {
+                break;
+            }
+            Some(Input::Character('g')) => {
+                state = state.jump_to_line(&init_pos);
+            }
+            // h - Open help page
+            Some(Input::Character('h')) => {
+                state = state.help_menu();
+            }
+            // j - Move down one line
+            Some(Input::Character('j')) | Some(Input::KeyDown) => {
+                let new_pos: i32 = state.content_top + 1;
+                state = state.jump_to_line(&new_pos);
+            }
+            // k - Move up one line
+            Some(Input::Character('k')) | Some(Input::KeyUp) => {
+                let new_pos: i32 = state.content_top - 1;
+                state = state.jump_to_line(&new_pos);
+            }
+            // d, PgDn - Move down half screen
+            Some(Input::Character('d')) | Some(Input::KeyNPage) => {
+                let half_screen_down: i32 = min(
+                    state.content_top + (state.pane_height / 2),
+                    state.content_len,
+                );
+                state = state.jump_to_line(&half_screen_down);
+            }
+            // u, PgUp - Move up half screen
+            Some(Input::Character('u')) | Some(Input::KeyPPage) => {
+                let half_screen_up: i32 = max(0, state.content_top - (state.pane_height / 2));
+                state = state.jump_to_line(&half_screen_up);
+            }
+            // o - Open new file
+            Some(Input::Character('o')) => {
+                state = state.open_file();
+            }
+            // / - Search
+            Some(Input::Character('/')) => {
+                state = state.search(false);
+            }
+            // ? - Reverse search
+            Some(Input::Character('?')) => {
+                state = state.search(true);
+            }
+            // n - Jump to next search result
+            Some(Input::Character('n')) => {
+                state = state.jump_to_next_search_result();
+            }
+            // N - Jump to last search result
+            Some(Input::Character('N')) => {
+                state = state.jump_to_last_search_result();
+            }
+            // v - Edit the current file
+            Some(Input::Character('v')) => {
+                state = state.edit_file();
+            }
+            // s - Save lines to a file
+            Some(Input::Character('s')) => {
+                state = state.save_to_file();
+            }
+            // Mouse wheel and clicks
+            Some(Input::KeyMouse) => {
+                state = state.handle_mouse();
+            }
+            // M - Toggle mouse capture
+            Some(Input::Character('M')) => {
+                let enabled: bool = !state.mouse_enabled;
+                state = state.set_mouse(enabled);
+                state.show_message(if enabled {
+                    "Mouse capture on"
+                } else {
+                    "Mouse capture off, the terminal can select text"
+                });
+            }
+            // Ctrl-W - Pane commands, followed by:
+            //   s, v - Split stacked/side by side
+            //   w, W - Focus the next/previous pane
+            //   c, q - Close the pane
+            //   +, - - Make the pane taller/shorter
+            //   >, < - Make the pane wider/narrower
+            Some(Input::Character('\u{17}')) => {
+                state = match state.window.getch() {
+                    Some(Input::Character('s')) => state.split_pane(false),
+                    Some(Input::Character('v')) => state.split_pane(true),
+                    Some(Input::Character('w')) | Some(Input::Character('\u{17}')) => {
+                        state.cycle_panes(true)
+                    }
+                    Some(Input::Character('W')) => state.cycle_panes(false),
+                    Some(Input::Character('c')) | Some(Input::Character('q')) => state.close_pane(),
+                    Some(Input::Character('+')) => state.resize_pane(false, 5),
+                    Some(Input::Character('-')) => state.resize_pane(false, -5),
+                    Some(Input::Character('>')) => state.resize_pane(true, 5),
+                    Some(Input::Character('<')) => state.resize_pane(true, -5),
+                    _ => state,
+                };
+            }
+            // V - Select lines in visual mode
+            Some(Input::Character('V')) => {
+                state = state.start_visual();
+            }
+            // m - Set the mark at the current line
+            Some(Input::Character('m')) => {
+                state = state.set_mark();
+            }
+            // | - Pipe lines to a shell command
+            Some(Input::Character('|')) => {
+                state = state.pipe_to_command();
+            }
+            // [, ] - Switch to the previous/next open buffer
+            Some(Input::Character('[')) => {
+                state = state.cycle_buffers(false);
+            }
+            Some(Input::Character(']')) => {
+                state = state.cycle_buffers(true);
+            }
+            // D - Diff the current buffer against a file
+            Some(Input::Character('D')) => {
+                state = state.diff_file();
+            }
+            // {, } - Jump to the previous/next hunk of a diff
+            Some(Input::Character('{')) => {
+                state = state.jump_to_hunk(false);
+            }
+            Some(Input::Character('}')) => {
+                state = state.jump_to_hunk(true);
+            }
+            // J - Toggle JSON mode
+            Some(Input::Character('J')) => {
+                state = state.toggle_json();
+            }
+            // z, Z - Fold/unfold the JSON array or object at the current line, or all of them
+            Some(Input::Character('z')) => {
+                state = state.toggle_fold(false);
+            }
+            Some(Input::Character('Z')) => {
+                state = state.toggle_fold(true);
+            }
+            // T - Toggle table mode
+            Some(Input::Character('T')) => {
+                state = state.toggle_table();
+            }
+            // Left, Right - Scroll a table by a column
+            Some(Input::KeyLeft) => {
+                state = state.scroll_columns(-1);
+            }
+            Some(Input::KeyRight) => {
+                state = state.scroll_columns(1);
+            }
+            // c - Search one column of a table
+            Some(Input::Character('c')) => {
+                state = state.search_column();
+            }
+            // t - Jump to a time in a log file
+            Some(Input::Character('t')) => {
+                state = state.jump_to_time();
+            }
+            // L - Hide log lines below a level
+            Some(Input::Character('L')) => {
+                state = state.filter_levels();
+            }
+            // e, E - Jump to the next/previous WARN or ERROR line
+            Some(Input::Character('e')) => {
+                state = state.jump_to_problem(true);
+            }
+            Some(Input::Character('E')) => {
+                state = state.jump_to_problem(false);
+            }
+            // p - Pin a pattern to keep highlighted
+            Some(Input::Character('p')) => {
+                state = state.pin_pattern();
+            }
+            // P - List and remove pinned patterns
+            Some(Input::Character('P')) => {
+                state = state.manage_pins();
+            }
+            // F - Search every open buffer
+            Some(Input::Character('F')) => {
+                state = state.global_search();
+            }
+            // Esc u - Clear search highlighting (Esc is a prefix, so it waits for the next key if u didn't come with it, ex: Alt-u)
+            Some(Input::Character('\u{1b}')) => {
+                let sequence: Option<String> = state.read_escape_sequence().or_else(|| match state
+                    .window
+                    .getch()
+                {
+                    Some(Input::Character(c)) => Some(c.to_string()),
+                    _ => None,
+                });
+                if sequence.as_deref() == Some("u") {
+                    state = state.clear_search_highlight();
+                }
+            }
+            // Any other keys - do nothing
+            Some(_input) => (),
+            // Nothing left to read - quit
+            None if state.window.closed() => break,
+            None => (),
+        }
+    }
+    state.window.endwin();
+}
+
+/*
+                                Window Sizing & Coordinates
+                 0,0-----------------------------------------------------0,s_w
+                   |                                                     |
+                   |                                                     |
+                   |                                                     |
+                   |                                                     |
+                   |                                                     |
+                   |                                                     |
+                   |                                                     |
+                   |                                                     |
+                   |                                                     |
+                   |                                input:               |
+               s_h,0-----------------------------------------------------s_h,s_w
+*/
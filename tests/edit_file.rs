@@ -0,0 +1,70 @@
+// The stand-in editor is a shell script
+#![cfg(unix)]
+
+extern crate least;
+
+use std::{
+    env, fs,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    sync::Once,
+};
+
+use least::{HeadlessBackend, Pager};
+
+static EDITOR: Once = Once::new();
+
+// A file of its own under the system's temp dir
+// Also sets $VISUAL to a stand-in for vi that leaves the file alone and writes the +line it was given to <file>.line
+fn temp_file(name: &str, contents: &str) -> PathBuf {
+    let dir: PathBuf = env::temp_dir().join(format!("least-edit-{}", std::process::id()));
+    EDITOR.call_once(|| {
+        fs::create_dir_all(&dir).unwrap();
+        let editor: PathBuf = dir.join("vi");
+        fs::write(&editor, "#!/bin/sh\necho \"$1\" > \"$2.line\"\n").unwrap();
+        fs::set_permissions(&editor, fs::Permissions::from_mode(0o755)).unwrap();
+        env::set_var("VISUAL", &editor);
+    });
+    let file: PathBuf = dir.join(name);
+    fs::write(&file, contents).unwrap();
+    file
+}
+
+// Page a file after the given keys, and return the screen along with the line the editor was opened at
+fn edit(file: &Path, keys: &str) -> (HeadlessBackend, String) {
+    let screen: HeadlessBackend = HeadlessBackend::new(10, 40);
+    screen.keys(keys);
+    Pager::from_path(&file.to_string_lossy()).run_with(screen.clone());
+    let line: String = fs::read_to_string(format!("{}.line", file.display())).unwrap();
+    (screen, line.trim().to_string())
+}
+
+#[test]
+fn editing_a_filtered_log_opens_the_line_on_screen_and_keeps_the_filter() {
+    let file: PathBuf = temp_file(
+        "app.log",
+        "INFO start\nINFO a\nERROR boom\n  at trace\nINFO b\nWARN careful\nINFO c\n",
+    );
+    let (screen, line) = edit(&file, "Lwjv");
+    assert_eq!(line, "+4");
+    assert_eq!(screen.row(0), "  at trace");
+    assert_eq!(screen.row(1), "WARN careful");
+}
+
+#[test]
+fn editing_a_table_opens_the_record_on_screen() {
+    let file: PathBuf = temp_file("people.csv", "name,note\nada,\"two\nlines\"\nbob,x\ncy,y\n");
+    let (screen, line) = edit(&file, "jv");
+    // bob's record starts on line 4, after ada's spans lines 2 and 3
+    assert_eq!(line, "+4");
+    assert!(screen.row(1).starts_with("bob "));
+}
+
+#[test]
+fn editing_json_opens_the_line_the_value_came_from() {
+    let file: PathBuf = temp_file("data.json", "{\"a\": [1, 2],\n \"b\": 3}\n");
+    // Pretty printed, "b": 3 is on the sixth line
+    let (screen, line) = edit(&file, "jjjjjv");
+    assert_eq!(line, "+2");
+    assert_eq!(screen.row(0), "  \"b\": 3");
+}
@@ -0,0 +1,277 @@
+extern crate least;
+
+use least::{
+    backend::{self, Input, MouseEvent},
+    HeadlessBackend, Pager,
+};
+
+// "line 1" to "line 100"
+fn numbered_lines() -> Vec<String> {
+    (1..=100).map(|n| format!("line {}", n)).collect()
+}
+
+// Page the numbered lines on a 10 row screen after the given keys, and return the screen
+fn run_keys(keys: &str) -> HeadlessBackend {
+    let screen: HeadlessBackend = HeadlessBackend::new(10, 40);
+    screen.keys(keys);
+    Pager::from_lines(numbered_lines()).run_with(screen.clone());
+    screen
+}
+
+// The colors a cell is drawn in
+fn colors(screen: &HeadlessBackend, y: i32, x: i32) -> Option<(i16, i16)> {
+    screen.pair_colors(screen.cell(y, x).pair)
+}
+
+#[test]
+fn shows_the_first_lines() {
+    let screen: HeadlessBackend = run_keys("");
+    assert_eq!(screen.row(0), "line 1");
+    assert_eq!(screen.row(8), "line 9");
+    assert_eq!(screen.pending_keys(), 0);
+}
+
+#[test]
+fn moves_down_and_back_to_the_top() {
+    assert_eq!(run_keys("jjj").row(0), "line 4");
+    assert_eq!(run_keys("jjjk").row(0), "line 3");
+    assert_eq!(run_keys("d").row(0), "line 5");
+    assert_eq!(run_keys("ddg").row(0), "line 1");
+}
+
+#[test]
+fn stops_at_q() {
+    let screen: HeadlessBackend = run_keys("qjjj");
+    assert_eq!(screen.row(0), "line 1");
+    assert_eq!(screen.pending_keys(), 3);
+}
+
+#[test]
+fn search_jumps_to_and_highlights_the_match() {
+    let screen: HeadlessBackend = run_keys("/line 5\n");
+    assert_eq!(screen.row(0), "line 5");
+    assert_eq!(screen.row(1), "line 6");
+    // The match jumped to is yellow, the others on screen are green
+    assert_eq!(
+        colors(&screen, 0, 0),
+        Some((backend::COLOR_BLACK, backend::COLOR_YELLOW))
+    );
+    assert_eq!(screen.row(5), "line 10");
+    assert_eq!(screen.cell(5, 0).pair, screen.cell(1, 0).pair);
+}
+
+#[test]
+fn n_and_shift_n_step_through_matches() {
+    let screen: HeadlessBackend = run_keys("/line 5\nn");
+    assert_eq!(screen.row(0), "line 50");
+    assert_eq!(
+        colors(&screen, 0, 0),
+        Some((backend::COLOR_BLACK, backend::COLOR_YELLOW))
+    );
+    assert_eq!(
+        colors(&screen, 1, 0),
+        Some((backend::COLOR_BLACK, backend::COLOR_GREEN))
+    );
+    assert_eq!(run_keys("/line 5\nnnN").row(0), "line 50");
+}
+
+#[test]
+fn search_wraps_around_the_end() {
+    let screen: HeadlessBackend = run_keys("/line 99\nn");
+    assert_eq!(screen.row(0), "line 99");
+    assert!(screen
+        .row(9)
+        .contains("search hit BOTTOM, continuing at TOP"));
+}
+
+#[test]
+fn search_reports_missing_patterns() {
+    let screen: HeadlessBackend = run_keys("/nothing\n");
+    assert_eq!(screen.row(0), "line 1");
+    assert!(screen.row(9).contains("Pattern not found"));
+}
+
+#[test]
+fn esc_u_clears_search_highlighting() {
+    let screen: HeadlessBackend = run_keys("/line 5\n\u{1b}u");
+    assert_eq!(screen.row(0), "line 5");
+    assert_eq!(screen.cell(0, 0).pair, screen.cell(1, 0).pair);
+}
+
+#[test]
+fn esc_cancels_a_prompt() {
+    let screen: HeadlessBackend = HeadlessBackend::new(10, 40);
+    screen.keys("/line 5\u{1b}");
+    screen.pause();
+    screen.keys("j");
+    Pager::from_lines(numbered_lines()).run_with(screen.clone());
+    assert_eq!(screen.row(0), "line 2");
+    assert_eq!(screen.row(9), "");
+}
+
+#[test]
+fn prompts_edit_their_input() {
+    let screen: HeadlessBackend = HeadlessBackend::new(10, 40);
+    screen.keys("/line 9x");
+    screen.key(Input::KeyBackspace);
+    screen.keys("\n");
+    Pager::from_lines(numbered_lines()).run_with(screen.clone());
+    assert_eq!(screen.row(0), "line 9");
+}
+
+#[test]
+fn panes_split_side_by_side() {
+    let screen: HeadlessBackend = run_keys("\u{17}vj");
+    // The new pane on the right gets the focus
+    assert_eq!(screen.row(0), format!("{:<19}|line 2", "line 1"));
+}
+
+#[test]
+fn mouse_wheel_scrolls() {
+    let screen: HeadlessBackend = HeadlessBackend::new(10, 40);
+    screen.mouse(MouseEvent::WheelDown);
+    screen.mouse(MouseEvent::WheelDown);
+    screen.mouse(MouseEvent::WheelUp);
+    Pager::from_lines(numbered_lines())
+        .wheel_lines(2)
+        .run_with(screen.clone());
+    assert_eq!(screen.row(0), "line 3");
+    assert!(screen.mouse_enabled());
+}
+
+#[test]
+fn clicking_a_line_underlines_it() {
+    let screen: HeadlessBackend = HeadlessBackend::new(10, 40);
+    screen.mouse(MouseEvent::Click { y: 2, x: 3 });
+    Pager::from_lines(numbered_lines()).run_with(screen.clone());
+    assert!(screen.row(9).contains("Line 3"));
+    assert_eq!(screen.cell(2, 0).attributes, backend::A_UNDERLINE);
+    assert_eq!(screen.cell(1, 0).attributes, backend::A_NORMAL);
+}
+
+#[test]
+fn mouse_is_ignored_with_capture_off() {
+    let screen: HeadlessBackend = HeadlessBackend::new(10, 40);
+    screen.mouse(MouseEvent::WheelDown);
+    Pager::from_lines(numbered_lines())
+        .mouse(false)
+        .run_with(screen.clone());
+    assert_eq!(screen.row(0), "line 1");
+}
+
+#[test]
+fn failing_to_open_a_file_keeps_the_current_one() {
+    let screen: HeadlessBackend = run_keys("jo/least/missing.txt\n");
+    assert_eq!(screen.row(0), "line 2");
+    assert_eq!(screen.row(9), "\"/least/missing.txt\" does not exist");
+}
+
+#[test]
+fn visual_mode_past_the_end_selects_the_last_line() {
+    // Half pages down until the last line has scrolled off the top
+    let screen: HeadlessBackend = run_keys(&format!("{}V|cat\n", "d".repeat(30)));
+    assert_eq!(screen.row(9), "line 100");
+}
+
+#[test]
+fn visual_mode_needs_lines_to_select() {
+    let screen: HeadlessBackend = HeadlessBackend::new(10, 40);
+    screen.keys("V");
+    Pager::from_lines(Vec::new()).run_with(screen.clone());
+    assert_eq!(screen.row(9), "Nothing to select");
+}
+
+#[test]
+fn truncated_json_is_shown_as_text() {
+    let screen: HeadlessBackend = HeadlessBackend::new(10, 40);
+    Pager::from_lines(vec![String::from(r#"["\"#)]).run_with(screen.clone());
+    assert_eq!(screen.row(0), r#"["\"#);
+}
+
+#[test]
+fn deeply_nested_json_is_shown_as_text() {
+    let screen: HeadlessBackend = HeadlessBackend::new(10, 40);
+    Pager::from_lines(vec!["[".repeat(200_000)]).run_with(screen.clone());
+    assert_eq!(screen.row(0), "[".repeat(40));
+}
+
+#[test]
+fn json_nested_to_the_limit_is_pretty_printed() {
+    let screen: HeadlessBackend = HeadlessBackend::new(10, 40);
+    Pager::from_lines(vec!["[".repeat(256) + &"]".repeat(256)]).run_with(screen.clone());
+    assert_eq!(screen.row(0), "[");
+    assert_eq!(screen.row(1), "  [");
+}
+
+#[test]
+fn column_search_finds_text_cut_off_in_a_long_field() {
+    let lines: Vec<String> = vec![
+        String::from("name,notes"),
+        format!("a,{}needle", "x".repeat(45)),
+        format!("b,{}needle", "x".repeat(36)),
+    ];
+    let screen: HeadlessBackend = HeadlessBackend::new(10, 60);
+    screen.keys("cnotes\nneedle\n");
+    Pager::from_lines(lines)
+        .name("notes.csv")
+        .run_with(screen.clone());
+    assert_eq!(screen.row(1), format!("a    | {}~", "x".repeat(39)));
+    // Entirely cut off, so the ~ is highlighted instead
+    assert_eq!(
+        colors(&screen, 1, 46),
+        Some((backend::COLOR_BLACK, backend::COLOR_YELLOW))
+    );
+    assert_eq!(screen.cell(1, 45).pair, 0);
+    // Partly cut off, so the highlight stops before the ~
+    assert_eq!(screen.row(2), format!("b    | {}nee~", "x".repeat(36)));
+    assert_eq!(screen.cell(2, 42).pair, 0);
+    assert_ne!(screen.cell(2, 43).pair, 0);
+    assert_ne!(screen.cell(2, 45).pair, 0);
+    assert_eq!(screen.cell(2, 46).pair, 0);
+}
+
+#[test]
+fn jumping_to_a_problem_keeps_the_last_search() {
+    let lines: Vec<String> = (1..=30)
+        .map(|n| match n % 10 {
+            5 => format!("WARN slow {}", n),
+            _ => format!("INFO step {}", n),
+        })
+        .collect();
+    let screen: HeadlessBackend = HeadlessBackend::new(10, 40);
+    // Search for step 2 (which matches 2 and 20-29), jump to the WARN on line 5, then step on through the search
+    screen.keys("/step 2\nen");
+    Pager::from_lines(lines).run_with(screen.clone());
+    assert_eq!(screen.row(0), "INFO step 20");
+    // The search is still what's highlighted
+    assert_eq!(
+        colors(&screen, 0, 5),
+        Some((backend::COLOR_BLACK, backend::COLOR_YELLOW))
+    );
+}
+
+#[test]
+fn column_search_matches_come_back_after_scrolling_past_them() {
+    let lines: Vec<String> = vec![
+        String::from("id,word,note"),
+        String::from("1,apple,x"),
+        String::from("2,pear,y"),
+        String::from("3,apricot,z"),
+    ];
+    let screen: HeadlessBackend = HeadlessBackend::new(10, 40);
+    screen.keys("cword\nap\n");
+    // Scroll the word column off to the left and back, then step to the second match
+    screen.key(Input::KeyRight);
+    screen.key(Input::KeyRight);
+    screen.key(Input::KeyLeft);
+    screen.key(Input::KeyLeft);
+    screen.keys("n");
+    Pager::from_lines(lines)
+        .name("fruit.csv")
+        .run_with(screen.clone());
+    assert_eq!(screen.row(1), "3  | apricot | z");
+    assert_eq!(
+        colors(&screen, 1, 5),
+        Some((backend::COLOR_BLACK, backend::COLOR_YELLOW))
+    );
+}
@@ -0,0 +1,74 @@
+extern crate least;
+
+use std::{env, fs, path::PathBuf};
+
+use least::{load_file, LoadError};
+
+// A directory of its own under the system's temp dir
+fn temp_dir(name: &str) -> PathBuf {
+    let dir: PathBuf = env::temp_dir().join(format!("least-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn reads_the_lines_of_a_file() {
+    let file: PathBuf = temp_dir("lines").join("lines.txt");
+    fs::write(&file, "one\ntwo\r\nthree").unwrap();
+    let lines: Vec<String> = load_file(&file.to_string_lossy()).unwrap();
+    assert_eq!(lines, vec!["one", "two", "three"]);
+}
+
+#[test]
+fn reads_text_that_is_not_utf8() {
+    let file: PathBuf = temp_dir("latin1").join("latin1.txt");
+    // "café" in Latin-1, then a line of binary
+    fs::write(&file, b"caf\xe9\n\xff\x00ok\r\n").unwrap();
+    let lines: Vec<String> = load_file(&file.to_string_lossy()).unwrap();
+    assert_eq!(lines, vec!["caf\u{fffd}", "\u{fffd}\u{0}ok"]);
+}
+
+#[test]
+fn expands_variables() {
+    let dir: PathBuf = temp_dir("expand");
+    fs::write(dir.join("notes.txt"), "note").unwrap();
+    env::set_var("LEAST_TEST_DIR", &dir);
+    assert_eq!(
+        load_file("$LEAST_TEST_DIR/notes.txt").unwrap(),
+        vec!["note"]
+    );
+}
+
+#[test]
+fn reports_missing_files() {
+    let file: PathBuf = temp_dir("missing").join("missing.txt");
+    match load_file(&file.to_string_lossy()) {
+        Err(e @ LoadError::NotFound(_)) => {
+            assert_eq!(
+                e.to_string(),
+                format!("\"{}\" does not exist", file.display())
+            )
+        }
+        other => panic!("expected NotFound, got {:?}", other),
+    }
+}
+
+#[test]
+fn reports_directories() {
+    let dir: PathBuf = temp_dir("directory");
+    match load_file(&dir.to_string_lossy()) {
+        Err(LoadError::IsDirectory(path)) => assert_eq!(path, dir.display().to_string()),
+        other => panic!("expected IsDirectory, got {:?}", other),
+    }
+}
+
+#[test]
+fn reports_unset_variables_instead_of_panicking() {
+    match load_file("$LEAST_TEST_UNSET/notes.txt") {
+        Err(e @ LoadError::Expand { .. }) => assert_eq!(
+            e.to_string(),
+            "Could not expand \"$LEAST_TEST_UNSET/notes.txt\": $LEAST_TEST_UNSET is not set"
+        ),
+        other => panic!("expected Expand, got {:?}", other),
+    }
+}